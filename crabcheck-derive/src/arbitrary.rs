@@ -0,0 +1,108 @@
+use {
+    crate::{
+        bounded_generics,
+        construct,
+        is_recursive,
+        variant_weight,
+    },
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    syn::{
+        Data,
+        DeriveInput,
+        Fields,
+        Ident,
+        parse_quote,
+    },
+};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded_generics(
+        &input.generics,
+        &[parse_quote!(::crabcheck::quickcheck::Arbitrary<__R>)],
+        true,
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => generate_fields(&quote!(Self), &data.fields, name),
+        Data::Enum(data) => {
+            let weights =
+                data.variants.iter().map(variant_weight).collect::<syn::Result<Vec<_>>>()?;
+            if weights.iter().all(|weight| *weight == 0) {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "deriving `Arbitrary` requires a variant with a non-zero weight",
+                ));
+            }
+
+            // Once the size runs out, only variants without recursive fields are picked
+            let base_weights = data
+                .variants
+                .iter()
+                .zip(&weights)
+                .map(|(variant, weight)| {
+                    let recursive =
+                        variant.fields.iter().any(|field| is_recursive(&field.ty, name));
+                    if recursive { 0 } else { *weight }
+                })
+                .collect::<Vec<_>>();
+            let count = weights.len();
+            let weights = if base_weights.iter().any(|weight| *weight > 0) {
+                quote!(if n == 0 { [#(#base_weights),*] } else { [#(#weights),*] })
+            } else {
+                quote!([#(#weights),*])
+            };
+
+            let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+                let ident = &variant.ident;
+                let value = generate_fields(&quote!(Self::#ident), &variant.fields, name);
+                quote!(#i => #value)
+            });
+
+            quote! {
+                let weights: [u32; #count] = #weights;
+                match ::crabcheck::quickcheck::weighted_index(rng, &weights) {
+                    #(#arms,)*
+                    _ => unreachable!(),
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(name, "`Arbitrary` cannot be derived for unions"));
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::crabcheck::quickcheck::Arbitrary<__R> for #name #ty_generics
+            #where_clause
+        {
+            fn generate(rng: &mut __R, n: usize) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/// Generates every field, splitting a smaller size between the recursive ones.
+fn generate_fields(path: &TokenStream2, fields: &Fields, name: &Ident) -> TokenStream2 {
+    let recursive = fields.iter().filter(|field| is_recursive(&field.ty, name)).count();
+    let smaller = match recursive {
+        0 | 1 => quote!(n.saturating_sub(1)),
+        _ => quote!(n.saturating_sub(1) / #recursive),
+    };
+    let values = fields
+        .iter()
+        .map(|field| {
+            if is_recursive(&field.ty, name) {
+                quote!(::crabcheck::quickcheck::Arbitrary::generate(rng, #smaller))
+            } else {
+                quote!(::crabcheck::quickcheck::Arbitrary::generate(rng, n))
+            }
+        })
+        .collect();
+    construct(path, fields, values)
+}
@@ -0,0 +1,105 @@
+use {
+    crate::{
+        bindings,
+        bounded_generics,
+        construct,
+        pattern,
+        pattern_as,
+    },
+    proc_macro2::TokenStream as TokenStream2,
+    quote::{
+        format_ident,
+        quote,
+    },
+    syn::{
+        Data,
+        DeriveInput,
+        Fields,
+        Ident,
+        parse_quote,
+    },
+};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded_generics(
+        &input.generics,
+        &[
+            parse_quote!(::crabcheck::quickcheck::Crossover<__R>),
+            parse_quote!(::core::clone::Clone),
+        ],
+        true,
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(&quote!(Self), &data.fields);
+            let other = pattern_as(&quote!(Self), &data.fields, &other_bindings(&data.fields));
+            let child = cross_fields(&quote!(Self), &data.fields);
+            quote! {
+                let #pattern = self;
+                let #other = other;
+                #child
+            }
+        },
+        Data::Enum(data) => {
+            // Values of the same variant are crossed field by field, others pick a parent
+            let arms =
+                data.variants.iter().filter(|variant| !variant.fields.is_empty()).map(|variant| {
+                    let ident = &variant.ident;
+                    let path = quote!(Self::#ident);
+                    let pattern = pattern(&path, &variant.fields);
+                    let other =
+                        pattern_as(&path, &variant.fields, &other_bindings(&variant.fields));
+                    let child = cross_fields(&path, &variant.fields);
+                    quote!((#pattern, #other) => #child)
+                });
+
+            quote! {
+                match (self, other) {
+                    #(#arms,)*
+                    _ => {
+                        if ::crabcheck::rand::Rng::random_bool(rng, 0.5) {
+                            ::core::clone::Clone::clone(self)
+                        } else {
+                            ::core::clone::Clone::clone(other)
+                        }
+                    },
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(name, "`Crossover` cannot be derived for unions"));
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::crabcheck::quickcheck::Crossover<__R> for #name #ty_generics
+            #where_clause
+        {
+            fn crossover(&self, other: &Self, rng: &mut __R) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/// The names the fields of `other` are bound to, next to `bindings(fields)` for `self`.
+fn other_bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len()).map(|i| format_ident!("__other{}", i)).collect()
+}
+
+/// Builds `path` by crossing each field of `self` with the same field of `other`.
+fn cross_fields(path: &TokenStream2, fields: &Fields) -> TokenStream2 {
+    let values = bindings(fields)
+        .iter()
+        .zip(other_bindings(fields))
+        .map(|(binding, other)| {
+            quote!(::crabcheck::quickcheck::Crossover::crossover(#binding, #other, rng))
+        })
+        .collect();
+    construct(path, fields, values)
+}
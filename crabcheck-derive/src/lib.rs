@@ -0,0 +1,193 @@
+//! Derive macros for `crabcheck`, re-exported from `crabcheck::quickcheck`, and the
+//! `#[crabcheck::property]` attribute.
+//!
+//! Variants of an enum can be weighted with `#[crabcheck(weight = N)]` (the default weight is 1,
+//! and a weight of 0 disables the variant). Fields that mention the type itself are recursive:
+//! they are generated with a smaller size, and variants without recursive fields are the only
+//! ones picked once the size reaches zero, so the depth of generated values is bounded.
+
+mod arbitrary;
+mod crossover;
+mod mutate;
+mod property;
+mod shrink;
+
+use {
+    proc_macro::TokenStream,
+    proc_macro2::{
+        TokenStream as TokenStream2,
+        TokenTree,
+    },
+    quote::{
+        format_ident,
+        quote,
+    },
+    syn::{
+        DeriveInput,
+        Fields,
+        GenericArgument,
+        Generics,
+        Ident,
+        ItemFn,
+        PathArguments,
+        Type,
+        TypeParamBound,
+        Variant,
+        parse_macro_input,
+        parse_quote,
+    },
+};
+
+#[proc_macro_derive(Arbitrary, attributes(crabcheck))]
+pub fn derive_arbitrary(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    arbitrary::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(Crossover, attributes(crabcheck))]
+pub fn derive_crossover(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    crossover::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Mutates one field of a struct or of the variant at hand, or switches between fieldless variants.
+///
+/// Type parameters are bounded by `Arbitrary` as well as `Mutate` and `Clone`. Bounds go on the
+/// parameters rather than on the field types, and the `Mutate` impls of containers such as
+/// `Vec<T>` generate new elements, so a field of type `Vec<T>` needs `T: Arbitrary`.
+#[proc_macro_derive(Mutate, attributes(crabcheck))]
+pub fn derive_mutate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    mutate::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(Shrink, attributes(crabcheck))]
+pub fn derive_shrink(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    shrink::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Turns a function into a test checking it as a property, such as
+/// `fn prop_insert_valid(t: Tree, k: i32, v: i32) -> bool`. The test generates every argument,
+/// and panics with the shrunk counterexample if the property fails. Failures are stored under
+/// `crabcheck-regressions/` in the crate, and checked first by every later run.
+///
+/// `#[property(config = EXPR)]` sets the `Config` of the run, and `#[property(runner = PATH)]`
+/// replaces `crabcheck::quickcheck::quickcheck_with` by another runner taking a `&Config` and the
+/// property.
+#[proc_macro_attribute]
+pub fn property(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut arguments = property::Arguments::default();
+    let parser = syn::meta::parser(|meta| arguments.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+    property::expand(arguments, function).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Adds `bounds` to every type parameter and, if `with_rng` is set, appends the `__R: Rng`
+/// parameter that the `Arbitrary`, `Mutate` and `Crossover` traits are generic over.
+pub(crate) fn bounded_generics(
+    generics: &Generics,
+    bounds: &[TypeParamBound],
+    with_rng: bool,
+) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.extend(bounds.iter().cloned());
+    }
+    if with_rng {
+        generics.params.push(parse_quote!(__R: ::crabcheck::rand::Rng));
+    }
+    generics
+}
+
+/// Whether `ty` mentions `Self` or the type being derived.
+pub(crate) fn is_recursive(ty: &Type, name: &Ident) -> bool {
+    fn mentions(tokens: TokenStream2, name: &Ident) -> bool {
+        tokens.into_iter().any(|token| {
+            match token {
+                TokenTree::Ident(ident) => ident == *name || ident == "Self",
+                TokenTree::Group(group) => mentions(group.stream(), name),
+                _ => false,
+            }
+        })
+    }
+    mentions(quote!(#ty), name)
+}
+
+/// Whether `ty` is `Box<Self>` or a box of the type being derived.
+pub(crate) fn is_boxed_self(ty: &Type, name: &Ident) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return false;
+    };
+    match arguments.args.first() {
+        Some(GenericArgument::Type(Type::Path(inner))) if segment.ident == "Box" => {
+            inner
+                .path
+                .segments
+                .last()
+                .is_some_and(|last| last.ident == *name || last.ident == "Self")
+        },
+        _ => false,
+    }
+}
+
+/// The weight given to a variant by `#[crabcheck(weight = N)]`, defaulting to 1.
+pub(crate) fn variant_weight(variant: &Variant) -> syn::Result<u32> {
+    let mut weight = 1;
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("crabcheck")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("weight") {
+                weight = meta.value()?.parse::<syn::LitInt>()?.base10_parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported crabcheck attribute, expected `weight = N`"))
+            }
+        })?;
+    }
+    Ok(weight)
+}
+
+/// The names bound to each field by `pattern`.
+pub(crate) fn bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len()).map(|i| format_ident!("__field{}", i)).collect()
+}
+
+/// A pattern destructuring `path` with the given `fields`, binding them to `bindings(fields)`.
+pub(crate) fn pattern(path: &TokenStream2, fields: &Fields) -> TokenStream2 {
+    pattern_as(path, fields, &bindings(fields))
+}
+
+/// A pattern destructuring `path` with the given `fields`, binding them to `bindings`.
+pub(crate) fn pattern_as(path: &TokenStream2, fields: &Fields, bindings: &[Ident]) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #bindings),* })
+        },
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+/// An expression building `path` from one expression per field.
+pub(crate) fn construct(
+    path: &TokenStream2,
+    fields: &Fields,
+    values: Vec<TokenStream2>,
+) -> TokenStream2 {
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #values),* })
+        },
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
@@ -0,0 +1,126 @@
+use {
+    crate::{
+        bindings,
+        bounded_generics,
+        construct,
+        pattern,
+    },
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    syn::{
+        Data,
+        DeriveInput,
+        Fields,
+        parse_quote,
+    },
+};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded_generics(
+        &input.generics,
+        // `Arbitrary` too, since mutating containers such as `Vec<T>` can generate new elements
+        &[
+            parse_quote!(::crabcheck::quickcheck::Arbitrary<__R>),
+            parse_quote!(::crabcheck::quickcheck::Mutate<__R>),
+            parse_quote!(::core::clone::Clone),
+        ],
+        true,
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(&quote!(Self), &data.fields);
+            let mutation = mutate_one_field(&quote!(Self), &data.fields);
+            quote! {
+                let #pattern = self;
+                #mutation
+            }
+        },
+        Data::Enum(data) => {
+            // Fieldless variants can only be mutated by switching to another fieldless variant
+            let units = data
+                .variants
+                .iter()
+                .filter(|variant| variant.fields.is_empty())
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    construct(&quote!(Self::#ident), &variant.fields, vec![])
+                })
+                .collect::<Vec<_>>();
+            let count = units.len();
+            let unit_arms = units.iter().enumerate().map(|(i, unit)| quote!(#i => #unit));
+            let switch_unit = quote! {
+                match ::crabcheck::rand::Rng::random_range(rng, 0..#count) {
+                    #(#unit_arms,)*
+                    _ => unreachable!(),
+                }
+            };
+
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let pattern = pattern(&quote!(Self::#ident), &variant.fields);
+                if variant.fields.is_empty() {
+                    quote!(#pattern => #switch_unit)
+                } else {
+                    let mutation = mutate_one_field(&quote!(Self::#ident), &variant.fields);
+                    quote!(#pattern => #mutation)
+                }
+            });
+
+            quote! {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(name, "`Mutate` cannot be derived for unions"));
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::crabcheck::quickcheck::Mutate<__R> for #name #ty_generics
+            #where_clause
+        {
+            fn mutate(&self, rng: &mut __R, n: usize) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+/// Mutates a randomly picked field of the value bound by `pattern`, cloning the others.
+fn mutate_one_field(path: &TokenStream2, fields: &Fields) -> TokenStream2 {
+    let bindings = bindings(fields);
+    if bindings.is_empty() {
+        return construct(path, fields, vec![]);
+    }
+
+    let count = bindings.len();
+    let arms = (0..count).map(|i| {
+        let values = bindings
+            .iter()
+            .enumerate()
+            .map(|(j, binding)| {
+                if i == j {
+                    quote!(::crabcheck::quickcheck::Mutate::mutate(#binding, rng, n))
+                } else {
+                    quote!(::core::clone::Clone::clone(#binding))
+                }
+            })
+            .collect();
+        let value = construct(path, fields, values);
+        quote!(#i => #value)
+    });
+
+    quote! {
+        match ::crabcheck::rand::Rng::random_range(rng, 0..#count) {
+            #(#arms,)*
+            _ => unreachable!(),
+        }
+    }
+}
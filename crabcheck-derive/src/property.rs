@@ -0,0 +1,111 @@
+use {
+    proc_macro2::TokenStream as TokenStream2,
+    quote::{
+        format_ident,
+        quote,
+    },
+    syn::{
+        Expr,
+        FnArg,
+        ItemFn,
+        Path,
+        meta::ParseNestedMeta,
+        parse_quote,
+        spanned::Spanned,
+    },
+};
+
+/// The largest number of arguments a property can take, that of the largest `Arbitrary` tuple.
+const MAX_ARGUMENTS: usize = 5;
+
+/// The arguments of `#[property(...)]`.
+pub(crate) struct Arguments {
+    config: Expr,
+    runner: Path,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        Arguments {
+            config: parse_quote!(::crabcheck::config::Config::default()),
+            runner: parse_quote!(::crabcheck::quickcheck::quickcheck_with),
+        }
+    }
+}
+
+impl Arguments {
+    pub(crate) fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("config") {
+            self.config = meta.value()?.parse()?;
+            Ok(())
+        } else if meta.path.is_ident("runner") {
+            self.runner = meta.value()?.parse()?;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported property attribute, expected `config` or `runner`"))
+        }
+    }
+}
+
+/// Turns `function` into a test running `arguments.runner` on a tuple of its arguments, and
+/// panicking with the result of the run unless it finished. Failures are stored in the regression
+/// file of the property unless the configuration names another one.
+pub(crate) fn expand(arguments: Arguments, mut function: ItemFn) -> syn::Result<TokenStream2> {
+    let signature = &function.sig;
+    if !signature.generics.params.is_empty() {
+        return Err(syn::Error::new(signature.generics.span(), "properties cannot be generic"));
+    }
+    let inputs = &signature.inputs;
+    if inputs.is_empty() || inputs.len() > MAX_ARGUMENTS {
+        return Err(syn::Error::new(
+            signature.span(),
+            format!("properties take between 1 and {} arguments", MAX_ARGUMENTS),
+        ));
+    }
+    let types = inputs
+        .iter()
+        .map(|input| {
+            match input {
+                FnArg::Typed(typed) => Ok(&typed.ty),
+                FnArg::Receiver(receiver) => {
+                    Err(syn::Error::new(receiver.span(), "properties cannot take `self`"))
+                },
+            }
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let bindings: Vec<_> = (0..types.len()).map(|i| format_ident!("__argument{}", i)).collect();
+
+    let name = &signature.ident;
+    let vis = &function.vis;
+    // Attributes such as `#[ignore]` or `#[should_panic]` apply to the test
+    let attrs = std::mem::take(&mut function.attrs);
+    let Arguments { config, runner } = arguments;
+    let property = if types.len() == 1 {
+        quote!(|#(#bindings)*: #(#types)*| #name(#(#bindings)*))
+    } else {
+        quote!(|(#(#bindings),*): (#(#types),*)| #name(#(#bindings),*))
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #[test]
+        #vis fn #name() {
+            #function
+
+            let mut config: ::crabcheck::config::Config = #config;
+            if config.regressions.is_none() {
+                config.regressions = ::core::option::Option::Some(
+                    ::crabcheck::regressions::path(
+                        ::core::env!("CARGO_MANIFEST_DIR"),
+                        ::core::module_path!(),
+                        ::core::stringify!(#name),
+                    ),
+                );
+            }
+            let result = #runner(&config, #property);
+            if result.status != ::crabcheck::quickcheck::ResultStatus::Finished {
+                ::core::panic!("{}", result);
+            }
+        }
+    })
+}
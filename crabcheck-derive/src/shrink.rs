@@ -0,0 +1,124 @@
+use {
+    crate::{
+        bindings,
+        bounded_generics,
+        construct,
+        is_boxed_self,
+        is_recursive,
+        pattern,
+    },
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    syn::{
+        Data,
+        DeriveInput,
+        Fields,
+        Ident,
+        parse_quote,
+    },
+};
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = bounded_generics(
+        &input.generics,
+        &[parse_quote!(::crabcheck::quickcheck::Shrink), parse_quote!(::core::clone::Clone)],
+        false,
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let pattern = pattern(&quote!(Self), &data.fields);
+            let shrinks = shrink_fields(&quote!(Self), &data.fields, name, vec![]);
+            quote! {
+                let #pattern = self;
+                #shrinks
+            }
+        },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+                let ident = &variant.ident;
+                let recursive = variant.fields.iter().any(|field| is_recursive(&field.ty, name));
+
+                // Fieldless variants declared earlier are simpler, and recursive variants can
+                // always shrink to a fieldless one
+                let simpler = data
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, other)| other.fields.is_empty() && (recursive || *j < i))
+                    .map(|(_, other)| {
+                        let other_ident = &other.ident;
+                        let unit = construct(&quote!(Self::#other_ident), &other.fields, vec![]);
+                        quote!(::std::boxed::Box::new(::core::iter::once(#unit)))
+                    })
+                    .collect();
+
+                let pattern = pattern(&quote!(Self::#ident), &variant.fields);
+                let shrinks = shrink_fields(&quote!(Self::#ident), &variant.fields, name, simpler);
+                quote!(#pattern => { #shrinks })
+            });
+            quote! {
+                match self {
+                    #(#arms,)*
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(name, "`Shrink` cannot be derived for unions"));
+        },
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::crabcheck::quickcheck::Shrink for #name #ty_generics #where_clause {
+            fn shrink(&self) -> ::std::boxed::Box<dyn ::core::iter::Iterator<Item = Self> + '_> {
+                #body
+            }
+        }
+    })
+}
+
+/// Chains the `simpler` candidates, the boxed recursive fields, and the shrinks of every field
+/// of the value bound by `pattern`.
+fn shrink_fields(
+    path: &TokenStream2,
+    fields: &Fields,
+    name: &Ident,
+    mut candidates: Vec<TokenStream2>,
+) -> TokenStream2 {
+    let bindings = bindings(fields);
+
+    for (field, binding) in fields.iter().zip(&bindings) {
+        if is_boxed_self(&field.ty, name) {
+            candidates.push(quote! {
+                ::std::boxed::Box::new(::core::iter::once(::core::clone::Clone::clone(&**#binding)))
+            });
+        }
+    }
+
+    for (i, binding) in bindings.iter().enumerate() {
+        let values = bindings
+            .iter()
+            .enumerate()
+            .map(|(j, other)| {
+                if i == j { quote!(__shrunk) } else { quote!(::core::clone::Clone::clone(#other)) }
+            })
+            .collect();
+        let value = construct(path, fields, values);
+        candidates.push(quote! {
+            ::std::boxed::Box::new(
+                ::crabcheck::quickcheck::Shrink::shrink(#binding).map(move |__shrunk| #value)
+            )
+        });
+    }
+
+    quote! {
+        let candidates: ::std::vec::Vec<
+            ::std::boxed::Box<dyn ::core::iter::Iterator<Item = Self> + '_>
+        > = ::std::vec![#(#candidates),*];
+        ::std::boxed::Box::new(candidates.into_iter().flatten())
+    }
+}
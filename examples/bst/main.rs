@@ -1,8 +1,13 @@
 use {
     bst::Tree,
-    crabcheck::quickcheck::{
-        Arbitrary,
-        Mutate,
+    crabcheck::{
+        config::Config,
+        quickcheck::{
+            Arbitrary,
+            Mutate,
+            RunResult,
+            Shrink,
+        },
     },
     rand::Rng,
     spec::{
@@ -16,13 +21,12 @@ use {
         prop_union_post,
         prop_union_valid,
     },
-    std::i32,
 };
 
 #[cfg(feature = "profiling")]
-use crabcheck::profiling::quickcheck;
+use crabcheck::profiling::quickcheck_with;
 #[cfg(not(feature = "profiling"))]
-use crabcheck::quickcheck::quickcheck;
+use crabcheck::quickcheck::quickcheck_with;
 
 pub mod bst;
 pub mod spec;
@@ -59,11 +63,9 @@ fn mut_tree<R: Rng>(rng: &mut R, t: &Tree, n: usize, lo: i32, hi: i32) -> Tree {
                 },
                 1 => {
                     // mutate the key between the left and right
-                    let left_root =
-                        if let Tree::T(_, k, _, _) = l.as_ref() { *k } else { i32::MIN.max(lo) };
+                    let left_root = if let Tree::T(_, k, _, _) = l.as_ref() { *k } else { lo };
 
-                    let right_root =
-                        if let Tree::T(_, k, _, _) = r.as_ref() { *k } else { i32::MAX.min(hi) };
+                    let right_root = if let Tree::T(_, k, _, _) = r.as_ref() { *k } else { hi };
 
                     if left_root + 1 >= right_root {
                         return Tree::E;
@@ -74,12 +76,12 @@ fn mut_tree<R: Rng>(rng: &mut R, t: &Tree, n: usize, lo: i32, hi: i32) -> Tree {
                 },
                 2 => {
                     // mutate the left tree
-                    let new_l = mut_tree(rng, &l, n - 1, lo, *k);
+                    let new_l = mut_tree(rng, l, n - 1, lo, *k);
                     Tree::T(Box::new(new_l), *k, *v, r.clone())
                 },
                 _ => {
                     // mutate the right tree
-                    let new_r = mut_tree(rng, &r, n - 1, *k + 1, hi);
+                    let new_r = mut_tree(rng, r, n - 1, *k + 1, hi);
                     Tree::T(l.clone(), *k, *v, Box::new(new_r))
                 },
             }
@@ -93,36 +95,127 @@ impl<R: Rng> Mutate<R> for Tree {
     }
 }
 
+impl Shrink for Tree {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Tree> + '_> {
+        match self {
+            Tree::E => Box::new(std::iter::empty()),
+            Tree::T(l, k, v, r) => {
+                // Subtrees are still search trees, so try replacing the node with them first
+                let subtrees = [Tree::E, *l.clone(), *r.clone()].into_iter();
+                let left = l.shrink().map(move |l| Tree::T(l, *k, *v, r.clone()));
+                let right = r.shrink().map(move |r| Tree::T(l.clone(), *k, *v, r));
+                let values = v.shrink().map(move |v| Tree::T(l.clone(), *k, v, r.clone()));
+                Box::new(subtrees.chain(left).chain(right).chain(values))
+            },
+        }
+    }
+}
+
+/// The properties that can be checked from the command line, by name.
+const PROPERTIES: &[(&str, fn(&Config) -> RunResult)] = &[
+    ("insert_valid", |config| quickcheck_with(config, |(t, k, v)| prop_insert_valid(t, k, v))),
+    ("delete_valid", |config| quickcheck_with(config, |(t, k)| prop_delete_valid(&t, k))),
+    ("union_valid", |config| quickcheck_with(config, |(t1, t2)| prop_union_valid(&t1, &t2))),
+    ("insert_post", |config| {
+        quickcheck_with(config, |(t, k, k2, v)| prop_insert_post(t, k, k2, v))
+    }),
+    ("delete_post", |config| quickcheck_with(config, |(t, k, k2)| prop_delete_post(t, k, k2))),
+    ("union_post", |config| quickcheck_with(config, |(t1, t2, k)| prop_union_post(&t1, &t2, k))),
+    ("insert_model", |config| quickcheck_with(config, |(t, k, v)| prop_insert_model(&t, k, v))),
+    ("delete_model", |config| quickcheck_with(config, |(t, k)| prop_delete_model(&t, k))),
+    ("union_model", |config| quickcheck_with(config, |(t1, t2)| prop_union_model(&t1, &t2))),
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
+    let names: Vec<&str> = PROPERTIES.iter().map(|(name, _)| *name).collect();
     if args.len() != 2 {
-        panic!("Usage: {} <input>", args[0]);
-    }
-    let input = &args[1];
-    let input = input.as_str();
-
-    let r = match input {
-        "insert_valid" => quickcheck(|(t, k, v)| prop_insert_valid(t, k, v)),
-        "delete_valid" => quickcheck(|(t, k)| prop_delete_valid(&t, k)),
-        "union_valid" => quickcheck(|(t1, t2)| prop_union_valid(&t1, &t2)),
-        "insert_post" => quickcheck(|(t, k, k2, v)| prop_insert_post(t, k, k2, v)),
-        "delete_post" => quickcheck(|(t, k, k2)| prop_delete_post(t, k, k2)),
-        "union_post" => quickcheck(|(t1, t2, k)| prop_union_post(&t1, &t2, k)),
-        "insert_model" => quickcheck(|(t, k, v)| prop_insert_model(&t, k, v)),
-        "delete_model" => quickcheck(|(t, k)| prop_delete_model(&t, k)),
-        "union_model" => quickcheck(|(t1, t2)| prop_union_model(&t1, &t2)),
-        _ => panic!("Unknown input"),
+        panic!("Usage: {} <{}>", args[0], names.join("|"));
+    }
+    let Some((_, run)) = PROPERTIES.iter().find(|(name, _)| *name == args[1]) else {
+        panic!("Unknown property {}, expected one of {}", args[1], names.join(", "));
     };
+    let config = Config::new().with_max_tests(20_000);
+
+    let r = run(&config);
 
     match r.status {
         crabcheck::quickcheck::ResultStatus::Finished => println!("All tests passed!"),
-        crabcheck::quickcheck::ResultStatus::Failed { .. } => println!("A test failed!"),
+        crabcheck::quickcheck::ResultStatus::Failed { .. } => {
+            println!("A test failed! Replay it with {:?}", r.replay.unwrap());
+        },
         crabcheck::quickcheck::ResultStatus::GaveUp => println!("Gave up!"),
         crabcheck::quickcheck::ResultStatus::TimedOut => println!("Timed out!"),
         crabcheck::quickcheck::ResultStatus::Aborted { error } => {
             println!("Aborted due to error: {}", error);
         },
+        crabcheck::quickcheck::ResultStatus::InsufficientCoverage { label, required, observed } => {
+            println!("Only {:.1}% {}, but expected {:.1}%", observed, label, required);
+        },
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The configuration of a property failing on the buggy search tree, storing its failures in
+    /// the temporary directory.
+    fn buggy(name: &str) -> Config {
+        let path = std::env::temp_dir().join(format!("crabcheck-bst-{}.jsonl", name));
+        Config::default().with_seed(0).with_regressions(path)
+    }
+
+    #[crabcheck::property]
+    fn insert_valid(t: Tree, k: i32, v: i32) -> Option<bool> {
+        prop_insert_valid(t, k, v)
+    }
+
+    #[crabcheck::property]
+    fn delete_valid(t: Tree, k: i32) -> Option<bool> {
+        prop_delete_valid(&t, k)
+    }
+
+    #[crabcheck::property]
+    fn union_valid(t1: Tree, t2: Tree) -> Option<bool> {
+        prop_union_valid(&t1, &t2)
+    }
+
+    // The search tree has a bug these properties find, which must not be stored in the
+    // regressions of the crate
+    #[crabcheck::property(config = buggy("insert_post"))]
+    #[should_panic(expected = "Failed after")]
+    fn insert_post(t: Tree, k: i32, k2: i32, v: i32) -> Option<bool> {
+        prop_insert_post(t, k, k2, v)
+    }
+
+    #[crabcheck::property]
+    fn delete_post(t: Tree, k: i32, k2: i32) -> Option<bool> {
+        prop_delete_post(t, k, k2)
+    }
+
+    #[crabcheck::property]
+    fn union_post(t1: Tree, t2: Tree, k: i32) -> Option<bool> {
+        prop_union_post(&t1, &t2, k)
+    }
+
+    // The search tree has a bug these properties find, which must not be stored in the
+    // regressions of the crate
+    #[crabcheck::property(config = buggy("insert_model"))]
+    #[should_panic(expected = "Failed after")]
+    fn insert_model(t: Tree, k: i32, v: i32) -> Option<bool> {
+        prop_insert_model(&t, k, v)
+    }
+
+    #[crabcheck::property]
+    fn delete_model(t: Tree, k: i32) -> Option<bool> {
+        prop_delete_model(&t, k)
+    }
+
+    #[crabcheck::property]
+    fn union_model(t1: Tree, t2: Tree) -> Option<bool> {
+        prop_union_model(&t1, &t2)
+    }
+}
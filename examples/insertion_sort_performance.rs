@@ -48,7 +48,7 @@ fn main() {
         },
         |input: Vec<i32>, _| {
             let comps = instrumented_insertion_sort(&mut input.clone());
-            ((comps as f64 / (input.len() * input.len()) as f64) * 1000000.0) as usize
+            comps as f64 / (input.len() * input.len()) as f64
         },
     );
 
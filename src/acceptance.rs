@@ -0,0 +1,207 @@
+use {
+    crate::rng::TestRng,
+    rand::Rng,
+    std::fmt::Debug,
+};
+
+/// What an acceptance strategy knows about a new seed found by a targeting or fuzzing loop.
+/// Feedback is measured with `Measure`, when its type has a measure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candidate {
+    /// The measure of the feedback of the candidate.
+    pub feedback: Option<f64>,
+    /// The measure of the best feedback of the pool, if it is not empty.
+    pub best: Option<f64>,
+    /// The measure of the feedback of the seed whose turn it is, which the candidate was most
+    /// likely mutated from.
+    pub parent: Option<f64>,
+    /// Whether the feedback of the candidate is better than that of every seed of the pool.
+    pub improves: bool,
+    /// Whether the feedback of the candidate is better than that of the worst seed of the pool,
+    /// or the pool is empty.
+    pub beats_worst: bool,
+    /// The number of seeds in the pool.
+    pub pool_size: usize,
+    /// The iteration of the loop the candidate was found at, starting from 1.
+    pub iteration: u64,
+}
+
+/// Decides which new seeds a targeting or fuzzing loop keeps in its pool.
+pub trait Acceptance: Debug + Send + Sync {
+    fn accept(&self, candidate: &Candidate, rng: &mut TestRng) -> bool;
+
+    /// The capacity the pool needs for this strategy to work, used when `Config::pool_capacity`
+    /// is not set.
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Keeps only seeds better than all others, so that the pool is a chain of improvements.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Improvement;
+
+impl Acceptance for Improvement {
+    fn accept(&self, candidate: &Candidate, _: &mut TestRng) -> bool {
+        candidate.improves
+    }
+}
+
+/// Keeps the `k` best seeds found so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopK {
+    pub k: usize,
+}
+
+impl Acceptance for TopK {
+    fn accept(&self, candidate: &Candidate, _: &mut TestRng) -> bool {
+        candidate.pool_size < self.k || candidate.beats_worst
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.k)
+    }
+}
+
+/// Threshold accepting: keeps seeds measuring no more than `threshold` below their parent, or
+/// below the best seed when there is no parent. Feedback without a measure is only kept when it
+/// improves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold {
+    pub threshold: f64,
+}
+
+impl Acceptance for Threshold {
+    fn accept(&self, candidate: &Candidate, _: &mut TestRng) -> bool {
+        let Some(feedback) = candidate.feedback else {
+            return candidate.improves;
+        };
+        match candidate.parent.or(candidate.best) {
+            Some(reference) => feedback >= reference - self.threshold,
+            None => true,
+        }
+    }
+}
+
+/// How the temperature of simulated annealing decreases over the iterations of a loop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cooling {
+    /// The temperature is multiplied by the factor at every iteration.
+    Exponential(f64),
+    /// The temperature at iteration `i` is the initial one divided by `i`.
+    Inverse,
+    /// The temperature at iteration `i` is the initial one divided by `ln(i + 1) + 1`.
+    Logarithmic,
+}
+
+/// Simulated annealing, as in targeted property-based testing: keeps seeds measuring at least as
+/// much as their parent (or the best seed), and worse ones with probability `exp(-Δ / t)` for a
+/// loss `Δ` and a temperature `t` cooling down over the iterations. Feedback without a measure is
+/// only kept when it improves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Annealing {
+    pub temperature: f64,
+    pub cooling: Cooling,
+}
+
+impl Annealing {
+    /// The temperature at `iteration`, starting from 1.
+    pub fn temperature(&self, iteration: u64) -> f64 {
+        let i = iteration.max(1) as f64;
+        match self.cooling {
+            Cooling::Exponential(factor) => self.temperature * factor.powf(i - 1.0),
+            Cooling::Inverse => self.temperature / i,
+            Cooling::Logarithmic => self.temperature / (i.ln_1p() + 1.0),
+        }
+    }
+}
+
+impl Default for Annealing {
+    fn default() -> Self {
+        Annealing { temperature: 1.0, cooling: Cooling::Exponential(0.99) }
+    }
+}
+
+impl Acceptance for Annealing {
+    fn accept(&self, candidate: &Candidate, rng: &mut TestRng) -> bool {
+        let Some(feedback) = candidate.feedback else {
+            return candidate.improves;
+        };
+        let Some(reference) = candidate.parent.or(candidate.best) else {
+            return true;
+        };
+        let loss = reference - feedback;
+        if loss <= 0.0 {
+            return true;
+        }
+        let temperature = self.temperature(candidate.iteration);
+        temperature > 0.0 && rng.random_bool((-loss / temperature).exp().clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        rand::SeedableRng,
+    };
+
+    #[test]
+    fn test_acceptance() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let candidate = Candidate {
+            feedback: Some(8.0),
+            best: Some(12.0),
+            parent: Some(10.0),
+            improves: false,
+            beats_worst: true,
+            pool_size: 3,
+            iteration: 1,
+        };
+        let empty =
+            Candidate { best: None, parent: None, improves: true, pool_size: 0, ..candidate };
+
+        assert!(!Improvement.accept(&candidate, &mut rng));
+        assert!(Improvement.accept(&empty, &mut rng));
+
+        assert!(TopK { k: 3 }.accept(&candidate, &mut rng));
+        assert!(!TopK { k: 3 }.accept(&Candidate { beats_worst: false, ..candidate }, &mut rng));
+        assert!(TopK { k: 4 }.accept(&Candidate { beats_worst: false, ..candidate }, &mut rng));
+
+        assert!(Threshold { threshold: 2.0 }.accept(&candidate, &mut rng));
+        assert!(!Threshold { threshold: 1.0 }.accept(&candidate, &mut rng));
+        assert!(Threshold { threshold: 0.0 }.accept(&empty, &mut rng));
+        let unmeasured = Candidate { feedback: None, best: None, parent: None, ..candidate };
+        assert!(!Threshold { threshold: 2.0 }.accept(&unmeasured, &mut rng));
+        assert!(
+            Threshold { threshold: 2.0 }
+                .accept(&Candidate { improves: true, ..unmeasured }, &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_annealing() {
+        let annealing = Annealing { temperature: 8.0, cooling: Cooling::Exponential(0.5) };
+        assert_eq!((annealing.temperature(1), annealing.temperature(3)), (8.0, 2.0));
+        let inverse = Annealing { cooling: Cooling::Inverse, ..annealing };
+        assert_eq!(inverse.temperature(4), 2.0);
+
+        // Worse seeds are kept less and less often as the temperature cools down
+        let mut rng = TestRng::seed_from_u64(0);
+        let worse = |iteration| {
+            Candidate {
+                feedback: Some(9.0),
+                best: Some(10.0),
+                parent: Some(10.0),
+                improves: false,
+                beats_worst: false,
+                pool_size: 1,
+                iteration,
+            }
+        };
+        let hot = (0..1000).filter(|_| annealing.accept(&worse(1), &mut rng)).count();
+        let cold = (0..1000).filter(|_| annealing.accept(&worse(10), &mut rng)).count();
+        assert!(hot > 800 && cold < 50, "{} {}", hot, cold);
+        assert!(annealing.accept(&Candidate { feedback: Some(11.0), ..worse(100) }, &mut rng));
+    }
+}
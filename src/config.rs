@@ -0,0 +1,349 @@
+use {
+    crate::{
+        acceptance::{
+            Acceptance,
+            Improvement,
+        },
+        feedback::{
+            Measure,
+            Measurer,
+            Nan,
+            Objective,
+            measurer,
+            measurers,
+        },
+        reporter::{
+            Human,
+            Reporter,
+        },
+        rng::{
+            Replay,
+            case_seed,
+            random_seed,
+        },
+        schedule::{
+            Exploit,
+            Schedule,
+        },
+        seedpool::{
+            Eviction,
+            Fingerprint,
+            hashed,
+        },
+    },
+    std::{
+        any::TypeId,
+        hash::Hash,
+        path::PathBuf,
+        ptr,
+        sync::Arc,
+        time::Duration,
+    },
+};
+
+/// Parameters shared by every runner in the crate.
+///
+/// The defaults follow QuickCheck: 100 passing tests, giving up after 10 discards per requested
+/// test, with sizes growing logarithmically from `start_size` up to `max_size`.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The number of passing tests (or loop iterations) after which a run is finished.
+    pub max_tests: u64,
+    /// A run gives up once more than `max_discard_ratio * max_tests` tests were discarded.
+    pub max_discard_ratio: u64,
+    /// The largest size passed to generators and mutators.
+    pub max_size: usize,
+    /// The size passed to generators and mutators for the first test.
+    pub start_size: usize,
+    /// The largest number of shrinking steps applied to a counterexample.
+    pub max_shrinks: u64,
+    /// The seed of the run, or `None` to pick a fresh random seed.
+    pub seed: Option<u64>,
+    /// A single test case to run instead of generating new ones, usually taken from a failure.
+    /// The targeting and fuzzing loops ignore it: their inputs derive from the seeds found by
+    /// earlier iterations, so a case cannot be rerun alone.
+    pub replay: Option<Replay>,
+    /// The probability with which the crossover loops, such as `crossover_targeting_loop_with`,
+    /// cross the two best seeds of their pool over instead of mutating one.
+    pub crossover_rate: f64,
+    /// The power schedule of the seed pools of the targeting and fuzzing loops.
+    pub schedule: Arc<dyn Schedule>,
+    /// The strategy deciding which new seeds the targeting and fuzzing loops keep in their pool.
+    pub acceptance: Arc<dyn Acceptance>,
+    /// The largest number of seeds the pools of the targeting and fuzzing loops hold, or `None`
+    /// for unbounded pools.
+    pub pool_capacity: Option<usize>,
+    /// Which seed a full pool evicts.
+    pub eviction: Eviction,
+    /// Fingerprints the inputs of the pools of the targeting and fuzzing loops, which then reject
+    /// inputs fingerprinted like the input of one of their seeds, if set.
+    pub fingerprint: Option<Fingerprint>,
+    /// What the property targeting and fuzzing loops, and the optimizing ones, optimize their
+    /// feedback for.
+    pub objective: Objective,
+    /// The wall-clock time after which the targeting and fuzzing loops stop, if any.
+    pub time_limit: Option<Duration>,
+    /// The CPU time of the thread running a targeting or fuzzing loop after which it stops, if any.
+    /// The time spent by other threads, such as those the property spawns, does not count.
+    pub cpu_time_limit: Option<Duration>,
+    /// The targeting and fuzzing loops stop once this many iterations in a row found no new best
+    /// seed, if set.
+    pub plateau: Option<u64>,
+    /// The targeting and fuzzing loops stop once their best feedback measures at least as well as
+    /// this value for `objective`, if set.
+    pub target: Option<f64>,
+    /// What the targeting and fuzzing loops do with feedback incomparable to itself, like NaN.
+    pub nan: Nan,
+    /// How the targeting and fuzzing loops measure each type of feedback, for the strategies that
+    /// need a measure. The `Measure` implementations of the crate are registered by default.
+    pub measures: Vec<(TypeId, Measurer)>,
+    /// Observes the events of runs, `Human` by default.
+    pub reporter: Arc<dyn Reporter>,
+    /// The file storing the past failures of the property, which `quickcheck` checks before any
+    /// new test and appends new failures to. Only `quickcheck_with`, `quickcheck_with_rng` and
+    /// `quickcheck_serialized_with` use it; the other runners ignore it.
+    pub regressions: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    pub fn with_max_tests(mut self, max_tests: u64) -> Config {
+        self.max_tests = max_tests;
+        self
+    }
+
+    pub fn with_max_discard_ratio(mut self, max_discard_ratio: u64) -> Config {
+        self.max_discard_ratio = max_discard_ratio;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Config {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_start_size(mut self, start_size: usize) -> Config {
+        self.start_size = start_size;
+        self
+    }
+
+    pub fn with_max_shrinks(mut self, max_shrinks: u64) -> Config {
+        self.max_shrinks = max_shrinks;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Config {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_replay(mut self, replay: Replay) -> Config {
+        self.replay = Some(replay);
+        self
+    }
+
+    pub fn with_crossover_rate(mut self, crossover_rate: f64) -> Config {
+        assert!((0.0..=1.0).contains(&crossover_rate), "the crossover rate must be a probability");
+        self.crossover_rate = crossover_rate;
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: impl Schedule + 'static) -> Config {
+        self.schedule = Arc::new(schedule);
+        self
+    }
+
+    pub fn with_acceptance(mut self, acceptance: impl Acceptance + 'static) -> Config {
+        self.acceptance = Arc::new(acceptance);
+        self
+    }
+
+    pub fn with_pool_capacity(mut self, pool_capacity: usize) -> Config {
+        self.pool_capacity = Some(pool_capacity);
+        self
+    }
+
+    pub fn with_eviction(mut self, eviction: Eviction) -> Config {
+        self.eviction = eviction;
+        self
+    }
+
+    pub fn with_fingerprint(mut self, fingerprint: Fingerprint) -> Config {
+        self.fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Deduplicates the inputs of the pools by their hash, for loops over inputs of type `T`.
+    pub fn with_hashing<T: Hash + 'static>(self) -> Config {
+        self.with_fingerprint(hashed::<T>)
+    }
+
+    pub fn with_objective(mut self, objective: Objective) -> Config {
+        self.objective = objective;
+        self
+    }
+
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Config {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    pub fn with_cpu_time_limit(mut self, cpu_time_limit: Duration) -> Config {
+        self.cpu_time_limit = Some(cpu_time_limit);
+        self
+    }
+
+    pub fn with_plateau(mut self, plateau: u64) -> Config {
+        self.plateau = Some(plateau);
+        self
+    }
+
+    pub fn with_target(mut self, target: f64) -> Config {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn with_nan(mut self, nan: Nan) -> Config {
+        self.nan = nan;
+        self
+    }
+
+    pub fn with_reporter(mut self, reporter: impl Reporter + 'static) -> Config {
+        self.reporter = Arc::new(reporter);
+        self
+    }
+
+    /// Registers the `Measure` implementation of the feedback type `F`, so that the loops can use
+    /// `Threshold` and `Annealing` acceptance, `Objective::Target` and `Config::target` with it.
+    pub fn with_measure<F: Measure + 'static>(mut self) -> Config {
+        self.measures.push(measurer::<F>());
+        self
+    }
+
+    pub fn with_regressions(mut self, regressions: impl Into<PathBuf>) -> Config {
+        self.regressions = Some(regressions.into());
+        self
+    }
+
+    /// The measurer registered for the feedback type `F`, if any.
+    pub fn measurer<F: 'static>(&self) -> Option<Measurer> {
+        let id = TypeId::of::<F>();
+        self.measures.iter().rev().find(|(other, _)| *other == id).map(|(_, measurer)| *measurer)
+    }
+
+    /// The seed used for a run with this configuration.
+    pub fn run_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(random_seed)
+    }
+
+    /// The `i`th test case (starting from 1) of the run seeded with `seed`, or the replayed case.
+    pub fn case(&self, seed: u64, i: u64) -> Replay {
+        self.replay.unwrap_or_else(|| Replay { seed: case_seed(seed, i), size: self.size(i) })
+    }
+
+    /// The number of discarded tests after which a run gives up.
+    pub fn max_discards(&self) -> u64 {
+        self.max_discard_ratio.saturating_mul(self.max_tests)
+    }
+
+    /// The size used for the `i`th test (starting from 1).
+    pub fn size(&self, i: u64) -> usize {
+        let growth = (i.max(1) as f32).log2() as usize;
+        self.start_size.saturating_add(growth).min(self.max_size)
+    }
+}
+
+/// Configurations are equal when their strategies, reporter and fingerprint are the same shared
+/// values, as trait objects cannot be compared otherwise, and they measure the same types of
+/// feedback.
+impl PartialEq for Config {
+    fn eq(&self, other: &Config) -> bool {
+        self.max_tests == other.max_tests
+            && self.max_discard_ratio == other.max_discard_ratio
+            && self.max_size == other.max_size
+            && self.start_size == other.start_size
+            && self.max_shrinks == other.max_shrinks
+            && self.seed == other.seed
+            && self.replay == other.replay
+            && self.crossover_rate == other.crossover_rate
+            && Arc::ptr_eq(&self.schedule, &other.schedule)
+            && Arc::ptr_eq(&self.acceptance, &other.acceptance)
+            && self.pool_capacity == other.pool_capacity
+            && self.eviction == other.eviction
+            && match (self.fingerprint, other.fingerprint) {
+                (Some(fingerprint), Some(other)) => ptr::fn_addr_eq(fingerprint, other),
+                (fingerprint, other) => fingerprint.is_none() && other.is_none(),
+            }
+            && self.objective == other.objective
+            && self.time_limit == other.time_limit
+            && self.cpu_time_limit == other.cpu_time_limit
+            && self.plateau == other.plateau
+            && self.target == other.target
+            && self.nan == other.nan
+            && self.measures.iter().map(|(id, _)| id).eq(other.measures.iter().map(|(id, _)| id))
+            && Arc::ptr_eq(&self.reporter, &other.reporter)
+            && self.regressions == other.regressions
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_tests: 100,
+            max_discard_ratio: 10,
+            max_size: 100,
+            start_size: 0,
+            max_shrinks: u64::MAX,
+            seed: None,
+            replay: None,
+            crossover_rate: 0.1,
+            schedule: Arc::new(Exploit::default()),
+            acceptance: Arc::new(Improvement),
+            pool_capacity: None,
+            eviction: Eviction::default(),
+            fingerprint: None,
+            objective: Objective::default(),
+            time_limit: None,
+            cpu_time_limit: None,
+            plateau: None,
+            target: None,
+            nan: Nan::default(),
+            measures: measurers(),
+            reporter: Arc::new(Human::default()),
+            regressions: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_is_bounded() {
+        let config = Config::new().with_start_size(3).with_max_size(5);
+        assert_eq!(config.size(1), 3);
+        assert_eq!(config.size(4), 5);
+        assert_eq!(config.size(u64::MAX), 5);
+    }
+
+    #[test]
+    fn test_equality() {
+        let config = Config::new().with_seed(1);
+        assert_eq!(config, config.clone().with_seed(1));
+        assert_ne!(config, config.clone().with_seed(2));
+        // Strategies are compared by identity
+        assert_ne!(config, config.clone().with_schedule(Exploit::default()));
+        assert_eq!(config.clone().with_hashing::<u8>(), config.clone().with_hashing::<u8>());
+        assert_ne!(config.clone().with_hashing::<u8>(), config.with_hashing::<u16>());
+    }
+
+    #[test]
+    #[should_panic(expected = "the crossover rate must be a probability")]
+    fn test_crossover_rate_is_a_probability() {
+        let _ = Config::new().with_crossover_rate(1.5);
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    any::{
+        Any,
+        TypeId,
+    },
+    cmp::{
+        Ordering,
+        Reverse,
+    },
+    fmt,
+    time::Duration,
+};
+
+/// Feedback that can be measured as a number, so that acceptance strategies can tell how much
+/// worse a seed is, not only that it is worse.
+///
+/// Measures must agree with the order of the feedback: greater feedback measures at least as
+/// much. The loops only measure feedback for the strategies that need it, `Threshold` and
+/// `Annealing` acceptance, `Objective::Target` and `Config::target`, and find the measure of
+/// feedback types outside the standard library through `Config::with_measure`.
+pub trait Measure {
+    fn measure(&self) -> f64;
+}
+
+/// Measures feedback whose type is only known to implement `Measure` at run time, failing on
+/// feedback of any other type.
+pub type Measurer = fn(&dyn Any) -> f64;
+
+/// The type of feedback `F` along with its measurer.
+pub(crate) fn measurer<F: Measure + 'static>() -> (TypeId, Measurer) {
+    (TypeId::of::<F>(), |feedback| feedback.downcast_ref::<F>().unwrap().measure())
+}
+
+/// The measurers of the `Measure` implementations of this module, as registered by default.
+pub(crate) fn measurers() -> Vec<(TypeId, Measurer)> {
+    macro_rules! measurers {
+        ($($t:ty),*) => {
+            vec![$(measurer::<$t>(), measurer::<Reverse<$t>>()),*]
+        };
+    }
+    measurers!(
+        i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, Duration
+    )
+}
+
+macro_rules! numeric_measure {
+    ($($t:ty),*) => {$(
+        impl Measure for $t {
+            fn measure(&self) -> f64 {
+                *self as f64
+            }
+        }
+    )*};
+}
+
+numeric_measure!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl Measure for bool {
+    fn measure(&self) -> f64 {
+        *self as u8 as f64
+    }
+}
+
+/// Durations are measured in seconds.
+impl Measure for Duration {
+    fn measure(&self) -> f64 {
+        self.as_secs_f64()
+    }
+}
+
+/// Reversed feedback is measured negatively, agreeing with its reversed order.
+impl<T: Measure> Measure for Reverse<T> {
+    fn measure(&self) -> f64 {
+        -self.0.measure()
+    }
+}
+
+/// What the targeting and fuzzing loops optimize their feedback for.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Objective {
+    /// The greater the feedback, the better.
+    #[default]
+    Maximize,
+    /// The smaller the feedback, the better.
+    Minimize,
+    /// The closer the measure of the feedback to the value, the better.
+    Target(f64),
+}
+
+impl Objective {
+    /// Orders two feedbacks from worse to better. Feedback incomparable to itself, like NaN, is
+    /// worse than any other, and other incomparable feedbacks are ordered by measure.
+    pub fn compare<F: PartialOrd + Measure>(&self, a: &F, b: &F) -> Ordering {
+        self.compare_scored(a, Some(self.score(a)), b, Some(self.score(b)))
+    }
+
+    /// Like `compare`, given the scores of the feedbacks if they have a measure. Feedbacks
+    /// without one are equal when they are incomparable, or when the objective is a target.
+    pub(crate) fn compare_scored<F: PartialOrd>(
+        &self,
+        a: &F,
+        a_score: Option<f64>,
+        b: &F,
+        b_score: Option<f64>,
+    ) -> Ordering {
+        let by_score = || {
+            match a_score.zip(b_score) {
+                Some((a, b)) => a.total_cmp(&b),
+                None => Ordering::Equal,
+            }
+        };
+        match (is_nan(a), is_nan(b)) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                match self {
+                    Objective::Maximize => a.partial_cmp(b).unwrap_or_else(by_score),
+                    Objective::Minimize => b.partial_cmp(a).unwrap_or_else(by_score),
+                    Objective::Target(_) => by_score(),
+                }
+            },
+        }
+    }
+
+    /// The measure of a feedback, negated or turned into the opposite of its distance to the
+    /// target so that better feedback always scores more. Feedback measuring NaN scores negative
+    /// infinity.
+    pub fn score<F: Measure>(&self, feedback: &F) -> f64 {
+        self.score_measure(feedback.measure())
+    }
+
+    /// The score of a feedback measuring `measure`, as given by `score`.
+    pub(crate) fn score_measure(&self, measure: f64) -> f64 {
+        if measure.is_nan() {
+            return f64::NEG_INFINITY;
+        }
+        match self {
+            Objective::Maximize => measure,
+            Objective::Minimize => -measure,
+            Objective::Target(target) => -(measure - target).abs(),
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Objective::Maximize => write!(f, "maximizing"),
+            Objective::Minimize => write!(f, "minimizing"),
+            Objective::Target(target) => write!(f, "targeting {}", target),
+        }
+    }
+}
+
+/// Feedback ordered by how well it meets an objective, which the loops keep in their pools so that
+/// pools, schedules and acceptance strategies only ever maximize.
+#[derive(Clone, Debug)]
+pub(crate) struct Oriented<F> {
+    pub(crate) feedback: F,
+    pub(crate) objective: Objective,
+    /// The score of the feedback for the objective, if it has a measure.
+    pub(crate) score: Option<f64>,
+}
+
+impl<F: 'static> Oriented<F> {
+    /// Orients `feedback`, measured by `measurer` if its type has one.
+    pub(crate) fn new(
+        feedback: F,
+        objective: Objective,
+        measurer: Option<Measurer>,
+    ) -> Oriented<F> {
+        let score = measurer.map(|measure| objective.score_measure(measure(&feedback)));
+        Oriented { feedback, objective, score }
+    }
+}
+
+impl<F: PartialOrd> PartialEq for Oriented<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: PartialOrd> Eq for Oriented<F> {}
+
+impl<F: PartialOrd> PartialOrd for Oriented<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: PartialOrd> Ord for Oriented<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.objective.compare_scored(&self.feedback, self.score, &other.feedback, other.score)
+    }
+}
+
+/// Whether `feedback` is incomparable to itself, like NaN.
+pub fn is_nan<F: PartialOrd>(feedback: &F) -> bool {
+    feedback.partial_cmp(feedback).is_none()
+}
+
+/// What the targeting and fuzzing loops do with feedback incomparable to itself, like NaN.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Nan {
+    /// The feedback is kept, as worse than any other whatever the objective.
+    #[default]
+    Worst,
+    /// The input is not added to the pool.
+    Discard,
+}
+
+impl Nan {
+    /// Whether a loop may add an input with `feedback` to its pool.
+    pub fn admits<F: PartialOrd>(&self, feedback: &F) -> bool {
+        *self == Nan::Worst || !is_nan(feedback)
+    }
+}
+
+/// Partially ordered feedback, like floats, ordered totally as by `Objective::Maximize`, so that
+/// seed pools built by hand can hold it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Total<F>(pub F);
+
+impl<F: PartialOrd + Measure> PartialEq for Total<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: PartialOrd + Measure> Eq for Total<F> {}
+
+impl<F: PartialOrd + Measure> PartialOrd for Total<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: PartialOrd + Measure> Ord for Total<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Objective::Maximize.compare(&self.0, &other.0)
+    }
+}
+
+impl<F: Measure> Measure for Total<F> {
+    fn measure(&self) -> f64 {
+        Objective::Maximize.score(&self.0)
+    }
+}
+
+/// Feedback made of several objectives, some of which can only improve at the expense of others.
+pub trait Dominance {
+    /// Whether `self` is at least as good as `other` in every objective, and better in one.
+    fn dominates(&self, other: &Self) -> bool;
+}
+
+/// Vectors compare objective by objective. Their lexicographic order extends dominance, so a pool
+/// ranking them still ranks a dominating seed above the seeds it dominates.
+impl<F: Ord> Dominance for Vec<F> {
+    fn dominates(&self, other: &Self) -> bool {
+        self.len() == other.len() && self != other && self.iter().zip(other).all(|(a, b)| a >= b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Orients `feedback` with its measure.
+    fn oriented<F: Measure + 'static>(feedback: F, objective: Objective) -> Oriented<F> {
+        Oriented::new(feedback, objective, Some(measurer::<F>().1))
+    }
+
+    #[test]
+    fn test_objectives() {
+        let best = |objective, feedbacks: &[u32]| {
+            feedbacks.iter().map(|feedback| oriented(*feedback, objective)).max().unwrap().feedback
+        };
+        let feedbacks = [4, 1, 9, 6];
+        assert_eq!(best(Objective::Maximize, &feedbacks), 9);
+        assert_eq!(best(Objective::Minimize, &feedbacks), 1);
+        assert_eq!(best(Objective::Target(5.5), &feedbacks), 6);
+        assert_eq!(Objective::Minimize.score(&3), -3.0);
+        assert_eq!(oriented(7, Objective::Target(5.0)).score, Some(-2.0));
+
+        // Feedback without a measure is ordered all the same
+        let unmeasured = |feedback| Oriented::new(feedback, Objective::Minimize, None);
+        assert!(unmeasured((1, 2)) > unmeasured((2, 0)));
+        assert_eq!(unmeasured((1, 2)).score, None);
+    }
+
+    #[test]
+    fn test_float_feedback() {
+        let mut feedbacks = [2.5, f64::NAN, -1.0, 0.5].map(Total);
+        feedbacks.sort();
+        assert!(feedbacks[0].0.is_nan());
+        assert_eq!(feedbacks[1..].iter().map(|x| x.0).collect::<Vec<_>>(), [-1.0, 0.5, 2.5]);
+        assert_eq!(Total(f64::NAN).measure(), f64::NEG_INFINITY);
+
+        // NaN is the worst feedback whatever the objective
+        let nan = oriented(f64::NAN, Objective::Minimize);
+        assert!(nan < oriented(f64::INFINITY, Objective::Minimize));
+        assert!(oriented(1.0, Objective::Minimize) > oriented(2.0, Objective::Minimize));
+        assert!(!Nan::Discard.admits(&f64::NAN) && Nan::Worst.admits(&f64::NAN));
+        assert!(Nan::Discard.admits(&vec![1.0, 2.0]) && !Nan::Discard.admits(&vec![1.0, f64::NAN]));
+    }
+
+    #[test]
+    fn test_dominance() {
+        assert!(vec![2, 3].dominates(&vec![2, 1]));
+        assert!(!vec![2, 3].dominates(&vec![2, 3]));
+        assert!(!vec![2, 3].dominates(&vec![3, 1]));
+        assert!(!vec![3, 1].dominates(&vec![2, 3]));
+    }
+}
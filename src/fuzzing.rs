@@ -1,53 +1,163 @@
-use rand::rngs::ThreadRng;
-
 use crate::quickcheck::ResultStatus;
 
 use {
     crate::{
+        config::Config,
+        feedback::{
+            Objective,
+            Oriented,
+        },
+        panic::catch,
         quickcheck::{
             Arbitrary,
+            Crossover,
             Mutate,
             RunResult,
+            Shrink,
+            shrink_failure,
+        },
+        reporter::{
+            Event,
+            report,
+            report_stop,
         },
+        rng::TestRng,
         seedpool::{
             Seed,
             SeedPool,
         },
+        statistics::{
+            Statistics,
+            record,
+        },
+        stopping::Stopping,
+        targeting::{
+            Cross,
+            Optimized,
+            add_seed,
+            feedback_measurer,
+            next_input,
+        },
+        testable::{
+            Property,
+            TestResult,
+            Testable,
+            check,
+            fails,
+            failure,
+        },
     },
+    rand::SeedableRng,
     std::fmt::Debug,
 };
 
 pub fn maximizing_fuzz_loop<
-    Domain: Clone + Arbitrary<ThreadRng> + Mutate<ThreadRng>,
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Seed<Domain, Feedback> {
+    maximizing_fuzz_loop_with(&Config::default().with_max_tests(1000), f, fb).into_best()
+}
+
+pub fn maximizing_fuzz_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
     Codomain,
-    Feedback: Clone + Ord + Debug,
+    Feedback: Clone + PartialOrd + Debug + 'static,
 >(
-    f: fn(Domain) -> Codomain,
-    fb: fn(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Optimized<Domain, Feedback> {
+    optimizing_fuzz_loop_with(&config.clone().with_objective(Objective::Maximize), f, fb)
+}
+
+pub fn minimizing_fuzz_loop<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
 ) -> Seed<Domain, Feedback> {
-    let mut pool: SeedPool<Domain, Feedback> = SeedPool::new();
-    let fuel = 1000;
-    let mut rng = rand::rng();
-
-    for i in 1..=fuel {
-        if i % 1000 == 0 {
-            println!("Iteration: {}", i);
-            println!("Pool size: {}", pool.size());
-            println!("Best of all time: {:?}", pool.best_of_all_time.clone().unwrap().feedback);
-            println!("====================\n");
+    minimizing_fuzz_loop_with(&Config::default().with_max_tests(1000), f, fb).into_best()
+}
+
+pub fn minimizing_fuzz_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Optimized<Domain, Feedback> {
+    optimizing_fuzz_loop_with(&config.clone().with_objective(Objective::Minimize), f, fb)
+}
+
+/// The fuzzing loop optimizing the feedback for `config.objective`, returning the best seed
+/// found.
+pub fn optimizing_fuzz_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Optimized<Domain, Feedback> {
+    fuzz_loop(config, None, f, fb)
+}
+
+/// Like `optimizing_fuzz_loop_with`, but crosses the two best seeds of the pool over instead of
+/// mutating one at `config.crossover_rate`.
+pub fn crossover_fuzz_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Crossover<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Optimized<Domain, Feedback> {
+    fuzz_loop(config, Some(Domain::crossover), f, fb)
+}
+
+fn fuzz_loop<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    cross: Option<Cross<Domain>>,
+    mut f: impl Property<Domain, Codomain>,
+    mut fb: impl FnMut(Box<dyn FnOnce() -> Codomain + '_>) -> (Codomain, Feedback),
+) -> Optimized<Domain, Feedback> {
+    let measurer = feedback_measurer::<Feedback>(config, &[config.objective], config.target);
+    let mut pool: SeedPool<Domain, Oriented<Feedback>> = SeedPool::from_config(config);
+    let mut rng = TestRng::seed_from_u64(config.run_seed());
+
+    let mut stopping = Stopping::new(config);
+    let mut i = 0;
+    let (stop, panic) = loop {
+        let best = pool.best_of_all_time.as_ref().and_then(|best| best.feedback.score);
+        if let Some(stop) = stopping.check(i, best) {
+            break (Some(stop), None);
         }
-        let input = if let Some(seed) = pool.pop() {
-            Domain::mutate(&seed.input, &mut rng, (i as f32).log2() as usize)
-        } else {
-            Domain::generate(&mut rng, (i as f32).log2() as usize)
-        };
+        i += 1;
+        let input = next_input(config, &mut pool, &mut rng, config.size(i), cross);
+        config.reporter.report(&Event::Started { test: i, input: &input });
 
         let copy = input.clone();
-        let (_, feedback) = fb(Box::new(move || f(copy)));
-
-        if pool.is_empty() || feedback > pool.best().clone().feedback {
-            let seed = Seed { input, feedback, energy: 1000 };
-            pool.add_seed(seed);
+        let property = &mut f;
+        let feedback = match catch(|| fb(Box::new(move || property(copy)))) {
+            Ok((_, feedback)) => Oriented::new(feedback, config.objective, measurer),
+            Err(panic) => break (None, Some((input, panic))),
+        };
+        if add_seed(config, &mut pool, &mut rng, i, input, feedback) {
+            stopping.improved(i);
         }
 
         #[cfg(feature = "profiling")]
@@ -55,51 +165,374 @@ pub fn maximizing_fuzz_loop<
             crate::profiling::snapshot(format!("iteration_{i}").as_str());
             crate::profiling::reset();
         }
-    }
+    };
 
-    pool.best_of_all_time.unwrap().clone()
+    config.reporter.report(&Event::Finished { tests: i, stop, result: None });
+    let stats = pool.stats();
+    let best = pool.best_of_all_time.map(|best| best.map_feedback(|oriented| oriented.feedback));
+    Optimized { best, pool: stats, stop, panic }
 }
 
 
 pub fn prop_fuzz_loop<
-    Domain: Clone + Debug + Arbitrary<ThreadRng> + Mutate<ThreadRng>,
-    Feedback: Clone + Ord + Debug,
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+>(
+    p: impl Property<Domain, P>,
+    fb: impl FnMut(Box<dyn FnOnce() -> P + '_>) -> (P, Feedback),
+) -> RunResult {
+    prop_fuzz_loop_with(&Config::default().with_max_tests(1000), p, fb)
+}
+
+pub fn prop_fuzz_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+>(
+    config: &Config,
+    p: impl Property<Domain, P>,
+    fb: impl FnMut(Box<dyn FnOnce() -> P + '_>) -> (P, Feedback),
+) -> RunResult {
+    prop_fuzz(config, None, p, fb)
+}
+
+/// Like `prop_fuzz_loop_with`, but crosses the two best seeds of the pool over instead of
+/// mutating one at `config.crossover_rate`.
+pub fn prop_crossover_fuzz_loop_with<Domain, Feedback, P>(
+    config: &Config,
+    p: impl Property<Domain, P>,
+    fb: impl FnMut(Box<dyn FnOnce() -> P + '_>) -> (P, Feedback),
+) -> RunResult
+where
+    Domain: Clone
+        + Debug
+        + Arbitrary<TestRng>
+        + Mutate<TestRng>
+        + Crossover<TestRng>
+        + Shrink
+        + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+{
+    prop_fuzz(config, Some(Domain::crossover), p, fb)
+}
+
+fn prop_fuzz<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
 >(
-    p: fn(Domain) -> bool,
-    fb: fn(Box<dyn FnOnce() -> bool + '_>) -> (bool, Feedback),
+    config: &Config,
+    cross: Option<Cross<Domain>>,
+    mut p: impl Property<Domain, P>,
+    mut fb: impl FnMut(Box<dyn FnOnce() -> P + '_>) -> (P, Feedback),
 ) -> RunResult {
-    let mut pool: SeedPool<Domain, Feedback> = SeedPool::new();
-    let fuel = 1000;
-    let mut rng = rand::rng();
-
-    for i in 1..=fuel {
-        if i % 1000 == 0 {
-            println!("Iteration: {}", i);
-            println!("Pool size: {}", pool.size());
-            println!("Best of all time: {:?}", pool.best_of_all_time.clone().unwrap().feedback);
-            println!("====================\n");
+    let measurer = feedback_measurer::<Feedback>(config, &[config.objective], config.target);
+    let mut pool: SeedPool<Domain, Oriented<Feedback>> = SeedPool::from_config(config);
+    let seed = config.run_seed();
+    let mut rng = TestRng::seed_from_u64(seed);
+    let mut discarded = 0;
+    let mut statistics = Statistics::default();
+
+    let mut stopping = Stopping::new(config);
+    let mut i = 0;
+    let stop = loop {
+        let best = pool.best_of_all_time.as_ref().and_then(|best| best.feedback.score);
+        if let Some(stop) = stopping.check(i, best) {
+            break stop;
         }
-        let input = if let Some(seed) = pool.pop() {
-            Domain::mutate(&seed.input, &mut rng, (i as f32).log2() as usize)
-        } else {
-            Domain::generate(&mut rng, (i as f32).log2() as usize)
+        i += 1;
+        let input = match catch(|| next_input(config, &mut pool, &mut rng, config.size(i), cross)) {
+            Ok(input) => input,
+            Err(panic) => {
+                return report(
+                    config,
+                    RunResult {
+                        passed: i - 1 - discarded,
+                        discarded,
+                        shrinks: 0,
+                        seed,
+                        replay: None,
+                        status: ResultStatus::Aborted { error: format!("generator {}", panic) },
+                        statistics,
+                        pool: Some(pool.stats()),
+                        stop: None,
+                    },
+                );
+            },
         };
 
+        config.reporter.report(&Event::Started { test: i, input: &input });
         let copy = input.clone();
-        let (result, feedback) = fb(Box::new(move || p(copy)));
-
-        if !result {
-            return RunResult {
-                passed: i,
-                discarded: 0,
-                status: ResultStatus::Failed { arguments: vec![format!("{:?}", input)] },
-            };
+        let property = &mut p;
+        // The property runs within its feedback, so its panics are caught apart from those of the
+        // feedback, which abort the run
+        let mut panicked = None;
+        let property_panic = &mut panicked;
+        let (result, labels) = record(|| {
+            catch(|| {
+                fb(Box::new(move || {
+                    catch(|| property(copy)).unwrap_or_else(|caught| {
+                        *property_panic = Some(caught);
+                        std::panic::resume_unwind(Box::new(()))
+                    })
+                }))
+            })
+        });
+        if let (Err(panic), None) = (&result, &panicked) {
+            return report(
+                config,
+                RunResult {
+                    passed: i - 1 - discarded,
+                    discarded,
+                    shrinks: 0,
+                    seed,
+                    replay: None,
+                    status: ResultStatus::Aborted { error: format!("feedback {}", panic) },
+                    statistics,
+                    pool: Some(pool.stats()),
+                    stop: None,
+                },
+            );
+        }
+
+        let feedback = match result.map(|(outcome, feedback)| (outcome.result(), feedback)) {
+            Ok((TestResult::Passed, feedback)) if panicked.is_none() => {
+                statistics.add(labels);
+                config.reporter.report(&Event::Passed { test: i });
+                feedback
+            },
+            // Discarded inputs give no feedback
+            Ok((TestResult::Discarded, _)) if panicked.is_none() => {
+                discarded += 1;
+                config.reporter.report(&Event::Discarded { test: i });
+                continue;
+            },
+            // The property failed or panicked
+            _ => {
+                let (input, shrinks) = shrink_failure(config, i, input, |candidate| {
+                    fails(&check(&mut p, candidate.clone()))
+                });
+                let checked = check(&mut p, input.clone());
+                return report(
+                    config,
+                    RunResult {
+                        passed: i - 1 - discarded,
+                        discarded,
+                        shrinks,
+                        seed,
+                        replay: None,
+                        status: failure(&input, checked),
+                        statistics,
+                        pool: Some(pool.stats()),
+                        stop: None,
+                    },
+                );
+            },
+        };
+        let feedback = Oriented::new(feedback, config.objective, measurer);
+        if add_seed(config, &mut pool, &mut rng, i, input, feedback) {
+            stopping.improved(i);
+        }
+    };
+
+    report_stop(
+        config,
+        RunResult {
+            passed: i - discarded,
+            discarded,
+            shrinks: 0,
+            seed,
+            replay: None,
+            status: ResultStatus::Finished,
+            statistics,
+            pool: Some(pool.stats()),
+            stop: None,
+        },
+        stop,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::stopping::Stop,
+        rand::Rng,
+        std::time::Duration,
+    };
+
+    /// Feedback that is the result of the property itself.
+    fn itself<T: Copy>(run: Box<dyn FnOnce() -> T + '_>) -> (T, T) {
+        let x = run();
+        (x, x)
+    }
+
+    #[test]
+    fn test_objectives_drive_fuzzing() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let sum = |x: Vec<u8>| x.iter().map(|v| *v as u64).sum::<u64>();
+
+        let maximized = maximizing_fuzz_loop_with(&config, sum, itself).best.unwrap();
+        let minimized = minimizing_fuzz_loop_with(&config, sum, itself).best.unwrap();
+        assert!(minimized.feedback < maximized.feedback);
+        assert_eq!(minimized.feedback, 0);
+        // The first input is generated at size 0
+        assert_eq!(minimizing_fuzz_loop(sum, itself).feedback, 0);
+
+        let targeted = optimizing_fuzz_loop_with(
+            &config.with_objective(Objective::Target(500.0)),
+            sum,
+            itself,
+        );
+        let best = targeted.best.unwrap();
+        assert!(best.feedback.abs_diff(500) < 10, "{:?}", best);
+    }
+
+    #[test]
+    fn test_fuzz_stopping_criteria() {
+        let config = Config::default().with_max_tests(10000).with_seed(0);
+
+        let result =
+            maximizing_fuzz_loop_with(&config.clone().with_target(200.0), |x: u8| x, itself);
+        assert_eq!(result.stop, Some(Stop::Target));
+        assert!(result.best.unwrap().feedback >= 200);
+
+        // Only the first seed improves on the feedback
+        let result =
+            prop_fuzz_loop_with(&config.clone().with_plateau(10), |_x: u8| true, |run| (run(), 0));
+        assert_eq!((result.status, result.passed), (ResultStatus::Finished, 11));
+        assert_eq!(result.stop, Some(Stop::Plateau));
+
+        let result = prop_fuzz_loop_with(
+            &config.with_time_limit(Duration::ZERO),
+            |_x: u8| true,
+            |run| (run(), 0),
+        );
+        assert_eq!((result.status, result.passed), (ResultStatus::TimedOut, 0));
+        assert_eq!(result.stop, Some(Stop::Time));
+    }
+
+    #[test]
+    fn test_prop_fuzz_panics() {
+        let config = Config::default().with_seed(0);
+        let result = prop_fuzz_loop_with(&config, |x: u8| assert!(x < 10), |run| (run(), 0));
+        let ResultStatus::Failed { arguments, panic: Some(panic), .. } = result.status else {
+            panic!("expected a panic, got {:?}", result.status);
+        };
+        assert_eq!(arguments, vec!["10".to_string()]);
+        assert!(panic.message.contains("x < 10"), "{}", panic);
+
+        // A panicking feedback aborts the run instead of being shrunk as a failure
+        let result = prop_fuzz_loop_with(
+            &config,
+            |_x: u8| true,
+            |run| -> (bool, u8) {
+                run();
+                panic!("no feedback")
+            },
+        );
+        let ResultStatus::Aborted { error } = result.status else {
+            panic!("expected an abort, got {:?}", result.status);
+        };
+        assert!(error.contains("no feedback"), "{}", error);
+    }
+
+    #[test]
+    fn test_prop_fuzz_loop() {
+        let result = prop_fuzz_loop(|_x: u8| true, |run| (run(), 0));
+        assert_eq!((result.status, result.passed), (ResultStatus::Finished, 1000));
+
+        // Every seed improves on the feedback of the seeds before it
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let mut calls = 0;
+        let result = prop_fuzz_loop_with(
+            &config,
+            |x: Vec<u8>| x.len() < 5,
+            |run| {
+                calls += 1;
+                (run(), calls)
+            },
+        );
+        let arguments = vec!["[0, 0, 0, 0, 0]".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+        assert!(result.shrinks > 0);
+    }
+
+    /// An input telling whether it is a child of two seeds.
+    #[derive(Clone, Debug)]
+    struct Child(u8, bool);
+
+    impl<R: Rng> Arbitrary<R> for Child {
+        fn generate(rng: &mut R, n: usize) -> Child {
+            Child(u8::generate(rng, n), false)
+        }
+    }
+
+    impl<R: Rng> Mutate<R> for Child {
+        fn mutate(&self, rng: &mut R, n: usize) -> Child {
+            Child(self.0.mutate(rng, n), false)
         }
-        if pool.is_empty() || feedback > pool.best().clone().feedback {
-            let seed = Seed { input, feedback, energy: 1000 };
-            pool.add_seed(seed);
+    }
+
+    impl<R: Rng> Crossover<R> for Child {
+        fn crossover(&self, other: &Child, _rng: &mut R) -> Child {
+            Child(self.0.max(other.0), true)
         }
     }
 
-    RunResult { passed: fuel, discarded: 0, status: ResultStatus::Finished }
+    impl Shrink for Child {
+        fn shrink(&self) -> Box<dyn Iterator<Item = Child> + '_> {
+            Box::new(self.0.shrink().map(|x| Child(x, false)))
+        }
+    }
+
+    #[test]
+    fn test_crossover_fuzz_loops() {
+        let config = Config::default().with_max_tests(100).with_seed(0).with_crossover_rate(1.0);
+        let feedback = |run: Box<dyn FnOnce() -> u8 + '_>| {
+            let x = run();
+            (x, x)
+        };
+
+        // Once the pool holds two seeds, every input is a child of the two best
+        let mut children = 0;
+        crossover_fuzz_loop_with(
+            &config,
+            |x: Child| {
+                children += x.1 as u64;
+                x.0
+            },
+            feedback,
+        );
+        assert!(children > 0);
+        let mut children = 0;
+        optimizing_fuzz_loop_with(
+            &config,
+            |x: Child| {
+                children += x.1 as u64;
+                x.0
+            },
+            feedback,
+        );
+        assert_eq!(children, 0);
+
+        // Every seed improves on the feedback of the seeds before it
+        let mut children = 0;
+        let mut calls = 0;
+        let result = prop_crossover_fuzz_loop_with(
+            &config,
+            |x: Child| {
+                children += x.1 as u64;
+                true
+            },
+            |run| {
+                calls += 1;
+                (run(), calls)
+            },
+        );
+        assert_eq!(result.status, ResultStatus::Finished);
+        assert_eq!(children, 98);
+    }
 }
@@ -1,10 +1,30 @@
+// Lets the derive macros refer to `::crabcheck` from within this crate as well
+extern crate self as crabcheck;
+
+pub mod acceptance;
+pub mod config;
+pub mod feedback;
 pub mod fuzzing;
+pub mod panic;
 pub mod parallelism;
 #[cfg(feature = "profiling")]
 pub mod profiling;
 pub mod quickcheck;
+pub mod regressions;
+pub mod reporter;
+pub mod rng;
+pub mod schedule;
 pub mod seedpool;
+pub mod statistics;
+pub mod stopping;
 pub mod targeting;
+pub mod testable;
+pub mod text;
 #[cfg(feature = "tracing")]
 pub mod tracing;
 pub mod utils;
+
+#[cfg(feature = "derive")]
+pub use crabcheck_derive::property;
+#[doc(hidden)]
+pub use rand;
@@ -0,0 +1,92 @@
+use std::{
+    cell::{
+        Cell,
+        RefCell,
+    },
+    fmt,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    sync::Once,
+};
+
+/// A panic caught while running a property or a generator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Panic {
+    pub message: String,
+    /// The `file:line:column` the panic was raised at, if known.
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {}: {}", location, self.message),
+            None => write!(f, "panicked: {}", self.message),
+        }
+    }
+}
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static CAUGHT: RefCell<Option<Panic>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Installs a panic hook that records panics raised inside `catch` instead of printing them,
+/// and defers to the previously installed hook everywhere else.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.get() {
+                let caught = Panic {
+                    message: payload_message(info.payload()),
+                    location: info.location().map(|location| location.to_string()),
+                };
+                CAUGHT.set(Some(caught));
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Runs `f`, turning a panic into an `Err` carrying its message and location.
+pub fn catch<T>(f: impl FnOnce() -> T) -> Result<T, Panic> {
+    install_hook();
+    let catching = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(catching);
+
+    result.map_err(|payload| {
+        CAUGHT
+            .take()
+            .unwrap_or_else(|| Panic { message: payload_message(&*payload), location: None })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch() {
+        let caught = catch(|| -> i32 { panic!("boom {}", 42) }).unwrap_err();
+        assert_eq!(caught.message, "boom 42");
+        assert!(caught.location.unwrap().starts_with("src/panic.rs:"));
+        assert_eq!(catch(|| 1), Ok(1));
+    }
+}
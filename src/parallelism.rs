@@ -1,67 +1,187 @@
 use std::{
     fmt::Debug,
     sync::{
-        Arc,
         Mutex,
-        atomic::AtomicBool,
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering,
+        },
     },
+    thread,
 };
 
-use rand::rngs::ThreadRng;
-
-use crate::quickcheck::{
-    Arbitrary,
-    ResultStatus,
-    RunResult,
+use crate::{
+    config::Config,
+    panic::catch,
+    quickcheck::{
+        Arbitrary,
+        ResultStatus,
+        RunResult,
+        Shrink,
+        shrink_failure,
+    },
+    reporter::{
+        Event,
+        report,
+    },
+    rng::TestRng,
+    statistics::{
+        Statistics,
+        record,
+    },
+    testable::{
+        ParProperty,
+        TestResult,
+        Testable,
+        check,
+        fails,
+        failure,
+    },
 };
 
-pub fn par_quickcheck<T: Arbitrary<ThreadRng> + Sync + Send + Debug + Clone + 'static>(
-    f: fn(&mut T) -> bool,
+pub fn par_quickcheck<T: Arbitrary<TestRng> + Shrink + Debug + Clone, P: Testable>(
+    f: impl ParProperty<T, P>,
 ) -> RunResult {
-    let result: Arc<Mutex<Option<RunResult>>> = Arc::new(Mutex::new(None));
-    let done = Arc::new(AtomicBool::new(false));
-    let mut threads = vec![];
+    par_quickcheck_with(&Config::default(), f)
+}
 
-    for _ in 0..4 {
-        let done = done.clone();
-        let result = result.clone();
-        let thread = std::thread::spawn(move || {
-            let mut rng = rand::rng();
-            for i in 0..100 {
-                if done.load(std::sync::atomic::Ordering::Relaxed) {
-                    return;
-                }
+pub fn par_quickcheck_with<T: Arbitrary<TestRng> + Shrink + Debug + Clone, P: Testable>(
+    config: &Config,
+    f: impl ParProperty<T, P>,
+) -> RunResult {
+    let seed = config.run_seed();
+    let result: Mutex<Option<RunResult>> = Mutex::new(None);
+    let done = AtomicBool::new(false);
+    let started = AtomicU64::new(0);
+    // Tests that passed or are still running, so that exactly `max_tests` tests pass; a
+    // discarded test gives its slot back
+    let claimed = AtomicU64::new(0);
+    let passed = AtomicU64::new(0);
+    let discarded = AtomicU64::new(0);
+    let statistics = Mutex::new(Statistics::default());
 
-                let mut input = T::generate(&mut rng, ((i + 1) as f32).log2() as usize);
-                match f(&mut input) {
-                    true => continue,
-                    false => {
-                        let mut result = result.lock().unwrap();
-                        *result = Some(RunResult {
-                            status: ResultStatus::Failed {
-                                arguments: vec![format!("{:?}", input)],
-                            },
-                            passed: i,
-                            discarded: 0,
-                        });
-                        done.store(true, std::sync::atomic::Ordering::Relaxed);
+    // Scoped threads can borrow the property, so it need not be `'static`
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                loop {
+                    if done.load(Ordering::Relaxed) {
                         return;
-                    },
-                }
-            }
-        });
-        threads.push(thread);
-    }
+                    }
 
-    for thread in threads {
-        thread.join().unwrap();
-    }
+                    let claim = claimed.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+                        (c < config.max_tests).then_some(c + 1)
+                    });
+                    let i = started.fetch_add(1, Ordering::Relaxed);
+                    // A replayed run consists of the replayed case only
+                    if claim.is_err() || (config.replay.is_some() && i > 0) {
+                        return;
+                    }
 
-    let result = result.lock().unwrap();
-    match &*result {
-        Some(result) => result.clone(),
-        None => RunResult { passed: 100, discarded: 0, status: ResultStatus::Finished },
-    }
+                    let case = config.case(seed, i + 1);
+                    let input: T = match catch(|| case.generate()) {
+                        Ok(input) => input,
+                        Err(panic) => {
+                            done.store(true, Ordering::Relaxed);
+                            *result.lock().unwrap() = Some(RunResult {
+                                status: ResultStatus::Aborted {
+                                    error: format!("generator {}", panic),
+                                },
+                                passed: passed.load(Ordering::Relaxed),
+                                discarded: discarded.load(Ordering::Relaxed),
+                                shrinks: 0,
+                                seed,
+                                replay: Some(case),
+                                statistics: Statistics::default(),
+                                pool: None,
+                                stop: None,
+                            });
+                            return;
+                        },
+                    };
+                    config.reporter.report(&Event::Started { test: i + 1, input: &input });
+                    let (checked, labels) = record(|| check(&f, &mut input.clone()));
+                    match checked {
+                        Ok(TestResult::Passed) => {
+                            passed.fetch_add(1, Ordering::Relaxed);
+                            statistics.lock().unwrap().add(labels);
+                            config.reporter.report(&Event::Passed { test: i + 1 });
+                        },
+                        Ok(TestResult::Discarded) => {
+                            claimed.fetch_sub(1, Ordering::Relaxed);
+                            config.reporter.report(&Event::Discarded { test: i + 1 });
+                            if discarded.fetch_add(1, Ordering::Relaxed) >= config.max_discards() {
+                                done.store(true, Ordering::Relaxed);
+                                result.lock().unwrap().get_or_insert(RunResult {
+                                    status: ResultStatus::GaveUp,
+                                    passed: passed.load(Ordering::Relaxed),
+                                    discarded: discarded.load(Ordering::Relaxed),
+                                    shrinks: 0,
+                                    seed,
+                                    replay: None,
+                                    statistics: Statistics::default(),
+                                    pool: None,
+                                    stop: None,
+                                });
+                                return;
+                            }
+                        },
+                        Ok(TestResult::Failed { .. }) | Err(_) => {
+                            done.store(true, Ordering::Relaxed);
+                            let (input, shrinks) =
+                                shrink_failure(config, i + 1, input, |candidate| {
+                                    fails(&check(&f, &mut candidate.clone()))
+                                });
+                            let checked = check(&f, &mut input.clone());
+                            *result.lock().unwrap() = Some(RunResult {
+                                status: failure(&input, checked),
+                                passed: passed.load(Ordering::Relaxed),
+                                discarded: discarded.load(Ordering::Relaxed),
+                                shrinks,
+                                seed,
+                                replay: Some(case),
+                                statistics: Statistics::default(),
+                                pool: None,
+                                stop: None,
+                            });
+                            return;
+                        },
+                    }
+                }
+            });
+        }
+    });
+
+    let statistics = statistics.into_inner().unwrap();
+    let result = result.into_inner().unwrap();
+    report(
+        config,
+        match result {
+            // Other threads may have finished more tests after the run stopped
+            Some(result) => {
+                RunResult {
+                    passed: passed.load(Ordering::Relaxed),
+                    discarded: discarded.load(Ordering::Relaxed),
+                    statistics,
+                    ..result
+                }
+            },
+            None => {
+                RunResult {
+                    passed: passed.load(Ordering::Relaxed),
+                    discarded: discarded.load(Ordering::Relaxed),
+                    shrinks: 0,
+                    seed,
+                    replay: None,
+                    status: ResultStatus::Finished,
+                    statistics,
+                    pool: None,
+                    stop: None,
+                }
+            },
+        },
+    )
 }
 
 #[cfg(test)]
@@ -5,15 +5,35 @@ use std::{
 };
 
 use {
-    rand::rngs::ThreadRng,
+    rand::SeedableRng,
     serde::Serialize,
 };
 
-use crate::quickcheck::{
-    Arbitrary,
-    Mutate,
-    ResultStatus,
-    RunResult,
+use crate::{
+    config::Config,
+    panic::catch,
+    quickcheck::{
+        Arbitrary,
+        Mutate,
+        ResultStatus,
+        RunResult,
+        Shrink,
+        shrink_failure,
+    },
+    reporter::report,
+    rng::TestRng,
+    statistics::{
+        Statistics,
+        record,
+    },
+    testable::{
+        Property,
+        TestResult,
+        Testable,
+        check,
+        fails,
+        failure,
+    },
 };
 
 
@@ -45,37 +65,88 @@ pub(crate) fn reset() {
     unsafe { __llvm_profile_reset_counters() };
 }
 
-pub fn quickcheck<T: Arbitrary<ThreadRng> + Mutate<ThreadRng> + Clone + Debug>(
-    f: fn(T) -> Option<bool>,
+pub fn quickcheck<T: Arbitrary<TestRng> + Mutate<TestRng> + Shrink + Clone + Debug, P: Testable>(
+    f: impl Property<T, P>,
 ) -> RunResult {
-    let mut rng = rand::rng();
+    quickcheck_with(&Config::default(), f)
+}
+
+pub fn quickcheck_with<
+    T: Arbitrary<TestRng> + Mutate<TestRng> + Shrink + Clone + Debug,
+    P: Testable,
+>(
+    config: &Config,
+    mut f: impl Property<T, P>,
+) -> RunResult {
+    let seed = config.run_seed();
+    let mut rng = TestRng::seed_from_u64(seed);
     tracing::debug!("Starting profiling quickcheck...");
-    let n = 20_000;
     let mut passed = 0;
     let mut discarded = 0;
-    for i in 0..n {
-        let input = T::generate(&mut rng, ((i + 1) as f32).log2() as usize);
+    let mut statistics = Statistics::default();
+    while passed < config.max_tests {
+        if discarded > config.max_discards() {
+            return report(
+                config,
+                RunResult {
+                    passed,
+                    discarded,
+                    shrinks: 0,
+                    seed,
+                    replay: None,
+                    status: ResultStatus::GaveUp,
+                    statistics,
+                    pool: None,
+                    stop: None,
+                },
+            );
+        }
+        let i = passed + discarded;
+        let case = config.case(seed, i + 1);
+        let input: T = match catch(|| case.generate()) {
+            Ok(input) => input,
+            Err(panic) => {
+                return report(
+                    config,
+                    RunResult {
+                        passed,
+                        discarded,
+                        shrinks: 0,
+                        seed,
+                        replay: Some(case),
+                        status: ResultStatus::Aborted { error: format!("generator {}", panic) },
+                        statistics,
+                        pool: None,
+                        stop: None,
+                    },
+                );
+            },
+        };
         tracing::trace!("Test #{}: {:?}", i, input);
-        match f(input.clone()) {
-            None => discarded += 1,
-            Some(true) => passed += 1,
-            Some(false) => {
+        let (checked, labels) = record(|| check(&mut f, input.clone()));
+        match checked {
+            Ok(TestResult::Discarded) => discarded += 1,
+            Ok(TestResult::Passed) => {
+                passed += 1;
+                statistics.add(labels);
+            },
+            Ok(TestResult::Failed { .. }) | Err(_) => {
                 let (mut positives, mut negatives) = (vec![], vec![(0, format!("{:?}", input))]);
                 crate::profiling::reset();
-                let _ = f(input.clone());
+                let _ = check(&mut f, input.clone());
                 crate::profiling::snapshot(format!("iteration_0").as_str());
 
                 for i in 1..=500 {
-                    let input = T::mutate(&input, &mut rng, ((i + 1) as f32).log2() as usize);
+                    let input = T::mutate(&input, &mut rng, config.size(i as u64 + 1));
                     crate::profiling::reset();
-                    let result = f(input.clone());
+                    let result = check(&mut f, input.clone());
                     crate::profiling::snapshot(format!("iteration_{i}").as_str());
                     match result {
-                        None => panic!("invalid mutation!"),
-                        Some(true) => {
+                        Ok(TestResult::Discarded) => panic!("invalid mutation!"),
+                        Ok(TestResult::Passed) => {
                             positives.push((i, format!("{:?}", input)));
                         },
-                        Some(false) => {
+                        Ok(TestResult::Failed { .. }) | Err(_) => {
                             negatives.push((i, format!("{:?}", input)));
                         },
                     }
@@ -104,15 +175,45 @@ pub fn quickcheck<T: Arbitrary<ThreadRng> + Mutate<ThreadRng> + Clone + Debug>(
                 fs::write(file_path, json).expect("Unable to write file");
                 tracing::debug!("JSON written to coverage/indices.json");
 
-
-                return RunResult {
-                    status: ResultStatus::Failed { arguments: vec![format!("{:?}", input)] },
-                    passed,
-                    discarded,
-                };
+                let (input, shrinks) = shrink_failure(config, i + 1, input, |candidate| {
+                    fails(&check(&mut f, candidate.clone()))
+                });
+                let checked = check(&mut f, input.clone());
+
+                return report(
+                    config,
+                    RunResult {
+                        status: failure(&input, checked),
+                        passed,
+                        discarded,
+                        shrinks,
+                        seed,
+                        replay: Some(case),
+                        statistics,
+                        pool: None,
+                        stop: None,
+                    },
+                );
             },
         }
+        // A replayed run consists of the replayed case only
+        if config.replay.is_some() {
+            break;
+        }
     }
 
-    RunResult { passed: n, discarded: 0, status: ResultStatus::Finished }
+    report(
+        config,
+        RunResult {
+            passed,
+            discarded,
+            shrinks: 0,
+            seed,
+            replay: None,
+            status: ResultStatus::Finished,
+            statistics,
+            pool: None,
+            stop: None,
+        },
+    )
 }
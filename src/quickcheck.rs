@@ -1,8 +1,76 @@
-use std::fmt::Debug;
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashMap,
+        HashSet,
+        VecDeque,
+    },
+    fmt::{
+        self,
+        Debug,
+    },
+    hash::{
+        DefaultHasher,
+        Hash,
+        Hasher,
+    },
+    iter,
+};
+
+use crate::{
+    config::Config,
+    panic::{
+        Panic,
+        catch,
+    },
+    regressions::{
+        self,
+        Codec,
+        Regression,
+    },
+    reporter::{
+        Event,
+        report,
+    },
+    rng::{
+        Replay,
+        TestRng,
+    },
+    seedpool::PoolStats,
+    statistics::{
+        Coverage,
+        Statistics,
+        record,
+    },
+    stopping::Stop,
+    testable::{
+        Property,
+        TestResult,
+        Testable,
+        check,
+        fails,
+        failure,
+    },
+};
 
-use rand::{
-    Rng,
-    rngs::ThreadRng,
+use {
+    rand::{
+        Rng,
+        SeedableRng,
+    },
+    serde::{
+        Serialize,
+        de::DeserializeOwned,
+    },
+};
+
+#[cfg(feature = "derive")]
+pub use crabcheck_derive::{
+    Arbitrary,
+    Crossover,
+    Mutate,
+    Shrink,
 };
 
 
@@ -14,28 +82,206 @@ pub trait Mutate<R: Rng> {
     fn mutate(&self, rng: &mut R, n: usize) -> Self;
 }
 
+/// Combines two values into a child, the genetic-algorithm counterpart of `Mutate` used by the
+/// targeting and fuzzing loops.
+///
+/// The default implementation picks one of the parents as a whole.
+pub trait Crossover<R: Rng>: Clone {
+    fn crossover(&self, other: &Self, rng: &mut R) -> Self {
+        if rng.random_bool(0.5) { self.clone() } else { other.clone() }
+    }
+}
 
-impl<R: Rng> Arbitrary<R> for i32 {
-    fn generate(rng: &mut R, n: usize) -> i32 {
-        rng.random_range(-(n as i32)..=(n as i32))
+/// Produces simpler variants of a value, used to minimize counterexamples.
+///
+/// Every candidate should be strictly simpler than `self`, so that greedy shrinking terminates.
+/// The default implementation produces no candidates, leaving the value as it is.
+pub trait Shrink: Sized {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self> + '_> {
+        Box::new(iter::empty())
     }
 }
 
-impl<R: Rng> Mutate<R> for i32 {
-    fn mutate(&self, rng: &mut R, _n: usize) -> i32 {
-        rng.random_range(self - 10..self + 10)
+
+/// The probability with which primitive generators pick one of their edge values (zero, one,
+/// the bounds of the type, ...) instead of a value within the size.
+const EDGE_PROBABILITY: f64 = 0.1;
+
+// Each type is sampled through a type of at least its width that `rand` can sample uniformly
+macro_rules! signed_impls {
+    ($($t:ty => $sample:ty),*) => {$(
+        impl<R: Rng> Arbitrary<R> for $t {
+            fn generate(rng: &mut R, n: usize) -> $t {
+                if rng.random_bool(EDGE_PROBABILITY) {
+                    let edges = [0, 1, -1, <$t>::MIN, <$t>::MAX];
+                    return edges[rng.random_range(0..edges.len())];
+                }
+                let bound = <$t>::try_from(n).unwrap_or(<$t>::MAX) as $sample;
+                rng.random_range(-bound..=bound) as $t
+            }
+        }
+
+        impl<R: Rng> Mutate<R> for $t {
+            fn mutate(&self, rng: &mut R, _n: usize) -> $t {
+                let lo = self.saturating_sub(10) as $sample;
+                let hi = self.saturating_add(10) as $sample;
+                rng.random_range(lo..=hi) as $t
+            }
+        }
+
+        impl Shrink for $t {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $t>> {
+                let x = *self;
+                let mut candidates = vec![];
+                if x != 0 {
+                    candidates.push(0);
+                }
+                if x < 0 && x != <$t>::MIN {
+                    candidates.push(-x);
+                }
+                // Approach `x` from zero by halving the distance
+                let mut i = x / 2;
+                while i != 0 {
+                    candidates.push(x - i);
+                    i /= 2;
+                }
+                Box::new(candidates.into_iter())
+            }
+        }
+    )*};
+}
+
+macro_rules! unsigned_impls {
+    ($($t:ty),*) => {$(
+        impl<R: Rng> Arbitrary<R> for $t {
+            fn generate(rng: &mut R, n: usize) -> $t {
+                if rng.random_bool(EDGE_PROBABILITY) {
+                    let edges = [0, 1, <$t>::MAX];
+                    return edges[rng.random_range(0..edges.len())];
+                }
+                let bound = <$t>::try_from(n).unwrap_or(<$t>::MAX);
+                rng.random_range(0..=bound)
+            }
+        }
+
+        impl<R: Rng> Mutate<R> for $t {
+            fn mutate(&self, rng: &mut R, _n: usize) -> $t {
+                rng.random_range(self.saturating_sub(10)..=self.saturating_add(10))
+            }
+        }
+
+        impl Shrink for $t {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $t>> {
+                let x = *self;
+                let mut candidates = vec![];
+                if x != 0 {
+                    candidates.push(0);
+                }
+                let mut i = x / 2;
+                while i != 0 {
+                    candidates.push(x - i);
+                    i /= 2;
+                }
+                Box::new(candidates.into_iter())
+            }
+        }
+    )*};
+}
+
+macro_rules! float_impls {
+    ($($t:ty),*) => {$(
+        impl<R: Rng> Arbitrary<R> for $t {
+            fn generate(rng: &mut R, n: usize) -> $t {
+                if rng.random_bool(EDGE_PROBABILITY) {
+                    let edges = [
+                        0.0,
+                        -0.0,
+                        1.0,
+                        -1.0,
+                        <$t>::MIN,
+                        <$t>::MAX,
+                        <$t>::MIN_POSITIVE,
+                        <$t>::EPSILON,
+                        <$t>::INFINITY,
+                        <$t>::NEG_INFINITY,
+                        <$t>::NAN,
+                    ];
+                    return edges[rng.random_range(0..edges.len())];
+                }
+                let bound = n as $t;
+                rng.random_range(-bound..=bound)
+            }
+        }
+
+        impl<R: Rng> Mutate<R> for $t {
+            fn mutate(&self, rng: &mut R, n: usize) -> $t {
+                if !self.is_finite() {
+                    return <$t>::generate(rng, n);
+                }
+                // Scale large values and nudge small ones, keeping the result finite
+                let mutated = self * rng.random_range(0.9..=1.1) + rng.random_range(-1.0..=1.0);
+                if mutated.is_finite() { mutated } else { *self }
+            }
+        }
+
+        impl Shrink for $t {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $t>> {
+                let x = *self;
+                let mut candidates = vec![];
+                if x == 0.0 {
+                    return Box::new(candidates.into_iter());
+                }
+                candidates.push(0.0);
+                if !x.is_finite() {
+                    return Box::new(candidates.into_iter());
+                }
+                if x < 0.0 {
+                    candidates.push(-x);
+                }
+                if x.trunc() != x {
+                    candidates.push(x.trunc());
+                }
+                candidates.push(x / 2.0);
+                Box::new(candidates.into_iter())
+            }
+        }
+    )*};
+}
+
+signed_impls!(i8 => i8, i16 => i16, i32 => i32, i64 => i64, i128 => i128, isize => i64);
+unsigned_impls!(u8, u16, u32, u64, u128, usize);
+float_impls!(f32, f64);
+
+impl<R: Rng> Arbitrary<R> for char {
+    fn generate(rng: &mut R, _n: usize) -> char {
+        if rng.random_bool(EDGE_PROBABILITY) {
+            let edges = ['\0', ' ', '\n', '\u{7f}', '\u{ff}', '\u{fffd}', char::MAX];
+            return edges[rng.random_range(0..edges.len())];
+        }
+        rng.random_range(' '..='~')
     }
 }
 
-impl<R: Rng> Arbitrary<R> for usize {
-    fn generate(rng: &mut R, n: usize) -> usize {
-        rng.random_range(0..=n)
+impl<R: Rng> Mutate<R> for char {
+    fn mutate(&self, rng: &mut R, _n: usize) -> char {
+        // Shifting into the surrogate range leaves the character as it is
+        let code = *self as u32;
+        let shifted = rng.random_range(code.saturating_sub(10)..=code.saturating_add(10));
+        char::from_u32(shifted).unwrap_or(*self)
     }
 }
 
-impl<R: Rng> Mutate<R> for usize {
-    fn mutate(&self, rng: &mut R, _n: usize) -> usize {
-        rng.random_range(self.saturating_sub(10)..=self.saturating_add(10))
+impl Shrink for char {
+    fn shrink(&self) -> Box<dyn Iterator<Item = char>> {
+        // Shrink towards 'a', passing through simpler characters of the same kind
+        let x = *self;
+        let candidates = [
+            Some('a'),
+            x.is_ascii_uppercase().then(|| x.to_ascii_lowercase()),
+            x.is_ascii_digit().then_some('0'),
+            x.is_whitespace().then_some(' '),
+        ];
+        Box::new(candidates.into_iter().flatten().filter(move |c| *c != x))
     }
 }
 
@@ -51,6 +297,23 @@ impl<R: Rng> Mutate<R> for bool {
     }
 }
 
+impl Shrink for bool {
+    fn shrink(&self) -> Box<dyn Iterator<Item = bool>> {
+        Box::new((*self).then_some(false).into_iter())
+    }
+}
+
+// Primitives have no parts to combine, so a child is one of its parents
+macro_rules! primitive_crossover {
+    ($($t:ty),*) => {$(
+        impl<R: Rng> Crossover<R> for $t {}
+    )*};
+}
+
+primitive_crossover!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, char, bool
+);
+
 impl<R: Rng, T: Arbitrary<R>> Arbitrary<R> for Vec<T> {
     fn generate(rng: &mut R, n: usize) -> Vec<T> {
         let mut list = Vec::with_capacity(n);
@@ -61,13 +324,265 @@ impl<R: Rng, T: Arbitrary<R>> Arbitrary<R> for Vec<T> {
     }
 }
 
-impl<R: Rng, T: Mutate<R> + Clone> Mutate<R> for Vec<T> {
+/// Relative weights of the operators `Mutate for Vec<T>` picks from.
+///
+/// `Mutate for Vec<T>` uses the default mix; a `Mutate` impl for a type wrapping a vector can
+/// pick its own with `VecMutation::mutate`. Operators that cannot apply to the list at hand (say,
+/// deleting from an empty list, or growing a list that already has `n` values) are skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VecMutation {
+    /// Mutate the values in a random slice.
+    pub modify: u32,
+    /// Insert a newly generated value.
+    pub insert: u32,
+    /// Delete a random slice.
+    pub delete: u32,
+    /// Insert a copy of a value next to it.
+    pub duplicate: u32,
+    /// Reverse a random slice.
+    pub reverse: u32,
+    /// Swap two values.
+    pub swap: u32,
+    /// Copy a random slice to another position.
+    pub splice: u32,
+}
+
+impl Default for VecMutation {
+    fn default() -> VecMutation {
+        VecMutation {
+            modify: 4,
+            insert: 2,
+            delete: 2,
+            duplicate: 1,
+            reverse: 1,
+            swap: 1,
+            splice: 1,
+        }
+    }
+}
+
+impl VecMutation {
+    pub fn mutate<R: Rng, T: Arbitrary<R> + Mutate<R> + Clone>(
+        &self,
+        list: &[T],
+        rng: &mut R,
+        n: usize,
+    ) -> Vec<T> {
+        let mut copy = list.to_vec();
+        let len = list.len();
+
+        // Operators only grow the list up to the size `n`, so repeated mutation stays bounded
+        let room = n.saturating_sub(len);
+        let nonempty = |weight: u32| if len > 0 { weight } else { 0 };
+        let growing = |weight: u32| if room > 0 { weight } else { 0 };
+        let weights = [
+            nonempty(self.modify),
+            growing(self.insert),
+            nonempty(self.delete),
+            nonempty(growing(self.duplicate)),
+            if len > 1 { self.reverse } else { 0 },
+            if len > 1 { self.swap } else { 0 },
+            nonempty(growing(self.splice)),
+        ];
+        if weights.iter().all(|weight| *weight == 0) {
+            return copy;
+        }
+
+        // Most operators work on a random slice `a..b`
+        let a = rng.random_range(0..=len);
+        let b = rng.random_range(a..=len);
+
+        match weighted_index(rng, &weights) {
+            0 => {
+                for value in copy[a..b].iter_mut() {
+                    *value = T::mutate(value, rng, n);
+                }
+            },
+            1 => copy.insert(a, T::generate(rng, n)),
+            2 => {
+                // Delete at least one value
+                let a = a.min(len - 1);
+                copy.drain(a..b.max(a + 1));
+            },
+            3 => {
+                let i = rng.random_range(0..len);
+                copy.insert(i, list[i].clone());
+            },
+            4 => copy[a..b].reverse(),
+            5 => copy.swap(rng.random_range(0..len), rng.random_range(0..len)),
+            _ => {
+                let at = rng.random_range(0..=len);
+                copy.splice(at..at, list[a..b.min(a + room)].iter().cloned());
+            },
+        }
+
+        copy
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R> + Mutate<R> + Clone> Mutate<R> for Vec<T> {
     fn mutate(&self, rng: &mut R, n: usize) -> Vec<T> {
+        VecMutation::default().mutate(self, rng, n)
+    }
+}
+
+impl<R: Rng, T: Clone> Crossover<R> for Vec<T> {
+    fn crossover(&self, other: &Vec<T>, rng: &mut R) -> Vec<T> {
+        // Single-point crossover: a prefix of `self` followed by the rest of `other`
+        let point = rng.random_range(0..=self.len().max(other.len()));
+        let mut child = self[..point.min(self.len())].to_vec();
+        child.extend_from_slice(&other[point.min(other.len())..]);
+        child
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Vec<T>> + '_> {
+        let len = self.len();
+
+        // First try removing chunks of halving sizes, then shrinking single elements
+        let chunks = iter::successors(Some(len), |k| Some(k / 2)).take_while(|k| *k > 0);
+        let removals = chunks.flat_map(move |k| {
+            (0..=len - k).step_by(k).map(move |start| {
+                let mut copy = self[..start].to_vec();
+                copy.extend_from_slice(&self[start + k..]);
+                copy
+            })
+        });
+        let elements = (0..len).flat_map(move |i| {
+            self[i].shrink().map(move |value| {
+                let mut copy = self.clone();
+                copy[i] = value;
+                copy
+            })
+        });
+
+        Box::new(removals.chain(elements))
+    }
+}
+
+
+impl<R: Rng, T: Arbitrary<R>> Arbitrary<R> for Box<T> {
+    fn generate(rng: &mut R, n: usize) -> Box<T> {
+        Box::new(T::generate(rng, n))
+    }
+}
+
+impl<R: Rng, T: Mutate<R>> Mutate<R> for Box<T> {
+    fn mutate(&self, rng: &mut R, n: usize) -> Box<T> {
+        Box::new(T::mutate(self, rng, n))
+    }
+}
+
+impl<T: Shrink> Shrink for Box<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Box<T>> + '_> {
+        Box::new(T::shrink(self).map(Box::new))
+    }
+}
+
+impl<R: Rng, T: Crossover<R>> Crossover<R> for Box<T> {
+    fn crossover(&self, other: &Box<T>, rng: &mut R) -> Box<T> {
+        Box::new(T::crossover(self, other, rng))
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R>> Arbitrary<R> for Option<T> {
+    fn generate(rng: &mut R, n: usize) -> Option<T> {
+        if rng.random_bool(0.25) { None } else { Some(T::generate(rng, n)) }
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R> + Mutate<R>> Mutate<R> for Option<T> {
+    fn mutate(&self, rng: &mut R, n: usize) -> Option<T> {
+        match self {
+            Some(value) if rng.random_bool(0.9) => Some(value.mutate(rng, n)),
+            Some(_) => None,
+            None => Some(T::generate(rng, n)),
+        }
+    }
+}
+
+impl<R: Rng, T: Crossover<R>> Crossover<R> for Option<T> {
+    fn crossover(&self, other: &Option<T>, rng: &mut R) -> Option<T> {
+        match (self, other) {
+            (Some(value), Some(other)) => Some(value.crossover(other, rng)),
+            _ => {
+                if rng.random_bool(0.5) {
+                    self.clone()
+                } else {
+                    other.clone()
+                }
+            },
+        }
+    }
+}
+
+impl<T: Shrink> Shrink for Option<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Option<T>> + '_> {
+        match self {
+            Some(value) => Box::new(iter::once(None).chain(value.shrink().map(Some))),
+            None => Box::new(iter::empty()),
+        }
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R>, E: Arbitrary<R>> Arbitrary<R> for Result<T, E> {
+    fn generate(rng: &mut R, n: usize) -> Result<T, E> {
+        if rng.random_bool(0.75) { Ok(T::generate(rng, n)) } else { Err(E::generate(rng, n)) }
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R> + Mutate<R>, E: Arbitrary<R> + Mutate<R>> Mutate<R> for Result<T, E> {
+    fn mutate(&self, rng: &mut R, n: usize) -> Result<T, E> {
+        // Mostly mutate the contents, occasionally switch to the other variant
+        let switch = rng.random_bool(0.1);
+        match self {
+            Ok(_) if switch => Err(E::generate(rng, n)),
+            Err(_) if switch => Ok(T::generate(rng, n)),
+            Ok(value) => Ok(value.mutate(rng, n)),
+            Err(error) => Err(error.mutate(rng, n)),
+        }
+    }
+}
+
+impl<R: Rng, T: Crossover<R>, E: Crossover<R>> Crossover<R> for Result<T, E> {
+    fn crossover(&self, other: &Result<T, E>, rng: &mut R) -> Result<T, E> {
+        match (self, other) {
+            (Ok(value), Ok(other)) => Ok(value.crossover(other, rng)),
+            (Err(error), Err(other)) => Err(error.crossover(other, rng)),
+            _ => {
+                if rng.random_bool(0.5) {
+                    self.clone()
+                } else {
+                    other.clone()
+                }
+            },
+        }
+    }
+}
+
+impl<T: Shrink, E: Shrink> Shrink for Result<T, E> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Result<T, E>> + '_> {
+        match self {
+            Ok(value) => Box::new(value.shrink().map(Ok)),
+            Err(error) => Box::new(error.shrink().map(Err)),
+        }
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R>, const N: usize> Arbitrary<R> for [T; N] {
+    fn generate(rng: &mut R, n: usize) -> [T; N] {
+        std::array::from_fn(|_| T::generate(rng, n))
+    }
+}
+
+impl<R: Rng, T: Mutate<R> + Clone, const N: usize> Mutate<R> for [T; N] {
+    fn mutate(&self, rng: &mut R, n: usize) -> [T; N] {
         let mut copy = self.clone();
 
-        // Pick a portion of the list and mutate it
-        let a = rng.random_range(0..=self.len());
-        let b = rng.random_range(a..=self.len());
+        // Pick a portion of the array and mutate it
+        let a = rng.random_range(0..=N);
+        let b = rng.random_range(a..=N);
 
         for value in copy[a..b].iter_mut() {
             *value = T::mutate(value, rng, n);
@@ -77,6 +592,220 @@ impl<R: Rng, T: Mutate<R> + Clone> Mutate<R> for Vec<T> {
     }
 }
 
+impl<R: Rng, T: Crossover<R>, const N: usize> Crossover<R> for [T; N] {
+    fn crossover(&self, other: &[T; N], rng: &mut R) -> [T; N] {
+        std::array::from_fn(|i| self[i].crossover(&other[i], rng))
+    }
+}
+
+impl<T: Shrink + Clone, const N: usize> Shrink for [T; N] {
+    fn shrink(&self) -> Box<dyn Iterator<Item = [T; N]> + '_> {
+        Box::new((0..N).flat_map(move |i| {
+            self[i].shrink().map(move |value| {
+                let mut copy = self.clone();
+                copy[i] = value;
+                copy
+            })
+        }))
+    }
+}
+
+/// Copies of a collection with chunks of halving sizes removed, the first candidates when
+/// shrinking any collection.
+fn chunk_removals<'a, I, C>(items: I) -> impl Iterator<Item = C> + 'a
+where
+    I: ExactSizeIterator + Clone + 'a,
+    C: FromIterator<I::Item>,
+{
+    let len = items.len();
+    let chunks = iter::successors(Some(len), |k| Some(k / 2)).take_while(|k| *k > 0);
+    chunks.flat_map(move |k| {
+        let items = items.clone();
+        (0..=len - k).step_by(k).map(move |start| {
+            let kept = items.clone().enumerate().filter(|(i, _)| *i < start || *i >= start + k);
+            kept.map(|(_, item)| item).collect()
+        })
+    })
+}
+
+impl<R: Rng, T: Arbitrary<R>> Arbitrary<R> for VecDeque<T> {
+    fn generate(rng: &mut R, n: usize) -> VecDeque<T> {
+        Vec::generate(rng, n).into()
+    }
+}
+
+impl<R: Rng, T: Arbitrary<R> + Mutate<R> + Clone> Mutate<R> for VecDeque<T> {
+    fn mutate(&self, rng: &mut R, n: usize) -> VecDeque<T> {
+        Vec::from(self.clone()).mutate(rng, n).into()
+    }
+}
+
+impl<R: Rng, T: Clone> Crossover<R> for VecDeque<T> {
+    fn crossover(&self, other: &VecDeque<T>, rng: &mut R) -> VecDeque<T> {
+        Vec::from(self.clone()).crossover(&Vec::from(other.clone()), rng).into()
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for VecDeque<T> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = VecDeque<T>> + '_> {
+        let elements = (0..self.len()).flat_map(move |i| {
+            self[i].shrink().map(move |value| {
+                let mut copy = self.clone();
+                copy[i] = value;
+                copy
+            })
+        });
+        Box::new(chunk_removals(self.iter().cloned()).chain(elements))
+    }
+}
+
+/// The `i`th of the items of an ordered collection.
+fn nth_ordered<T>(mut items: impl Iterator<Item = T>, i: usize) -> Option<T> {
+    items.nth(i)
+}
+
+/// The `i`th of the items of a hashed collection, ordered by a hash with fixed keys rather than by
+/// the random state of the collection, so that seeded runs pick the same items.
+fn nth_hashed<T: Hash>(items: impl Iterator<Item = T>, i: usize) -> Option<T> {
+    let mut hashed: Vec<(u64, T)> = items
+        .map(|item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            (hasher.finish(), item)
+        })
+        .collect();
+    hashed.sort_by_key(|(hash, _)| *hash);
+    hashed.into_iter().nth(i).map(|(_, item)| item)
+}
+
+macro_rules! map_impls {
+    ($map:ident, $nth:ident, $($key_bounds:tt)+) => {
+        impl<R: Rng, K: Arbitrary<R> + $($key_bounds)+, V: Arbitrary<R>> Arbitrary<R>
+            for $map<K, V>
+        {
+            fn generate(rng: &mut R, n: usize) -> $map<K, V> {
+                let len = rng.random_range(0..=n);
+                (0..len).map(|_| (K::generate(rng, n), V::generate(rng, n))).collect()
+            }
+        }
+
+        impl<R: Rng, K, V> Mutate<R> for $map<K, V>
+        where
+            K: Arbitrary<R> + Mutate<R> + Clone + $($key_bounds)+,
+            V: Arbitrary<R> + Mutate<R> + Clone,
+        {
+            fn mutate(&self, rng: &mut R, n: usize) -> $map<K, V> {
+                let mut copy = self.clone();
+                let key = $nth(self.keys(), rng.random_range(0..self.len().max(1)));
+                let Some((key, value)) = key.and_then(|key| self.get_key_value(key)) else {
+                    copy.insert(K::generate(rng, n), V::generate(rng, n));
+                    return copy;
+                };
+
+                match rng.random_range(0..4) {
+                    0 => {
+                        copy.insert(K::generate(rng, n), V::generate(rng, n));
+                    },
+                    1 => {
+                        copy.remove(key);
+                    },
+                    2 => {
+                        copy.insert(key.clone(), value.mutate(rng, n));
+                    },
+                    _ => {
+                        copy.remove(key);
+                        copy.insert(key.mutate(rng, n), value.clone());
+                    },
+                }
+
+                copy
+            }
+        }
+
+        impl<R: Rng, K: Clone + $($key_bounds)+, V: Clone> Crossover<R> for $map<K, V> {}
+
+        impl<K: Shrink + Clone + $($key_bounds)+, V: Shrink + Clone> Shrink for $map<K, V> {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $map<K, V>> + '_> {
+                // Remove entries first, then shrink values, then keys
+                let entries = self.iter().map(|(key, value)| (key.clone(), value.clone()));
+                let values = self.iter().flat_map(move |(key, value)| {
+                    value.shrink().map(move |value| {
+                        let mut copy = self.clone();
+                        copy.insert(key.clone(), value);
+                        copy
+                    })
+                });
+                let keys = self.iter().flat_map(move |(key, value)| {
+                    key.shrink().map(move |shrunk| {
+                        let mut copy = self.clone();
+                        copy.remove(key);
+                        copy.insert(shrunk, value.clone());
+                        copy
+                    })
+                });
+                Box::new(chunk_removals(entries).chain(values).chain(keys))
+            }
+        }
+    };
+}
+
+map_impls!(HashMap, nth_hashed, Eq + Hash);
+map_impls!(BTreeMap, nth_ordered, Ord);
+
+macro_rules! set_impls {
+    ($set:ident, $nth:ident, $($bounds:tt)+) => {
+        impl<R: Rng, T: Arbitrary<R> + $($bounds)+> Arbitrary<R> for $set<T> {
+            fn generate(rng: &mut R, n: usize) -> $set<T> {
+                let len = rng.random_range(0..=n);
+                (0..len).map(|_| T::generate(rng, n)).collect()
+            }
+        }
+
+        impl<R: Rng, T: Arbitrary<R> + Mutate<R> + Clone + $($bounds)+> Mutate<R> for $set<T> {
+            fn mutate(&self, rng: &mut R, n: usize) -> $set<T> {
+                let mut copy = self.clone();
+                let Some(value) = $nth(self.iter(), rng.random_range(0..self.len().max(1))) else {
+                    copy.insert(T::generate(rng, n));
+                    return copy;
+                };
+
+                match rng.random_range(0..3) {
+                    0 => {
+                        copy.insert(T::generate(rng, n));
+                    },
+                    1 => {
+                        copy.remove(value);
+                    },
+                    _ => {
+                        copy.remove(value);
+                        copy.insert(value.mutate(rng, n));
+                    },
+                }
+
+                copy
+            }
+        }
+
+        impl<R: Rng, T: Clone + $($bounds)+> Crossover<R> for $set<T> {}
+
+        impl<T: Shrink + Clone + $($bounds)+> Shrink for $set<T> {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $set<T>> + '_> {
+                let elements = self.iter().flat_map(move |value| {
+                    value.shrink().map(move |shrunk| {
+                        let mut copy = self.clone();
+                        copy.remove(value);
+                        copy.insert(shrunk);
+                        copy
+                    })
+                });
+                Box::new(chunk_removals(self.iter().cloned()).chain(elements))
+            }
+        }
+    };
+}
+
+set_impls!(HashSet, nth_hashed, Eq + Hash);
+set_impls!(BTreeSet, nth_ordered, Ord);
 
 impl<R: Rng, T1: Arbitrary<R>, T2: Arbitrary<R>> Arbitrary<R> for (T1, T2) {
     fn generate(rng: &mut R, n: usize) -> (T1, T2) {
@@ -178,6 +907,141 @@ impl<R: Rng, T1: Mutate<R>, T2: Mutate<R>, T3: Mutate<R>, T4: Mutate<R>, T5: Mut
     }
 }
 
+impl<R: Rng, T1: Crossover<R>, T2: Crossover<R>> Crossover<R> for (T1, T2) {
+    fn crossover(&self, other: &(T1, T2), rng: &mut R) -> (T1, T2) {
+        (self.0.crossover(&other.0, rng), self.1.crossover(&other.1, rng))
+    }
+}
+
+impl<R: Rng, T1: Crossover<R>, T2: Crossover<R>, T3: Crossover<R>> Crossover<R> for (T1, T2, T3) {
+    fn crossover(&self, other: &(T1, T2, T3), rng: &mut R) -> (T1, T2, T3) {
+        (
+            self.0.crossover(&other.0, rng),
+            self.1.crossover(&other.1, rng),
+            self.2.crossover(&other.2, rng),
+        )
+    }
+}
+
+impl<R: Rng, T1: Crossover<R>, T2: Crossover<R>, T3: Crossover<R>, T4: Crossover<R>> Crossover<R>
+    for (T1, T2, T3, T4)
+{
+    fn crossover(&self, other: &(T1, T2, T3, T4), rng: &mut R) -> (T1, T2, T3, T4) {
+        (
+            self.0.crossover(&other.0, rng),
+            self.1.crossover(&other.1, rng),
+            self.2.crossover(&other.2, rng),
+            self.3.crossover(&other.3, rng),
+        )
+    }
+}
+
+impl<
+    R: Rng,
+    T1: Crossover<R>,
+    T2: Crossover<R>,
+    T3: Crossover<R>,
+    T4: Crossover<R>,
+    T5: Crossover<R>,
+> Crossover<R> for (T1, T2, T3, T4, T5)
+{
+    fn crossover(&self, other: &(T1, T2, T3, T4, T5), rng: &mut R) -> (T1, T2, T3, T4, T5) {
+        (
+            self.0.crossover(&other.0, rng),
+            self.1.crossover(&other.1, rng),
+            self.2.crossover(&other.2, rng),
+            self.3.crossover(&other.3, rng),
+            self.4.crossover(&other.4, rng),
+        )
+    }
+}
+
+impl<T1: Shrink + Clone, T2: Shrink + Clone> Shrink for (T1, T2) {
+    fn shrink(&self) -> Box<dyn Iterator<Item = (T1, T2)> + '_> {
+        let s1 = self.0.shrink().map(move |x| (x, self.1.clone()));
+        let s2 = self.1.shrink().map(move |x| (self.0.clone(), x));
+        Box::new(s1.chain(s2))
+    }
+}
+
+impl<T1: Shrink + Clone, T2: Shrink + Clone, T3: Shrink + Clone> Shrink for (T1, T2, T3) {
+    fn shrink(&self) -> Box<dyn Iterator<Item = (T1, T2, T3)> + '_> {
+        let s1 = self.0.shrink().map(move |x| (x, self.1.clone(), self.2.clone()));
+        let s2 = self.1.shrink().map(move |x| (self.0.clone(), x, self.2.clone()));
+        let s3 = self.2.shrink().map(move |x| (self.0.clone(), self.1.clone(), x));
+        Box::new(s1.chain(s2).chain(s3))
+    }
+}
+
+impl<T1: Shrink + Clone, T2: Shrink + Clone, T3: Shrink + Clone, T4: Shrink + Clone> Shrink
+    for (T1, T2, T3, T4)
+{
+    fn shrink(&self) -> Box<dyn Iterator<Item = (T1, T2, T3, T4)> + '_> {
+        let s1 = self.0.shrink().map(move |x| (x, self.1.clone(), self.2.clone(), self.3.clone()));
+        let s2 = self.1.shrink().map(move |x| (self.0.clone(), x, self.2.clone(), self.3.clone()));
+        let s3 = self.2.shrink().map(move |x| (self.0.clone(), self.1.clone(), x, self.3.clone()));
+        let s4 = self.3.shrink().map(move |x| (self.0.clone(), self.1.clone(), self.2.clone(), x));
+        Box::new(s1.chain(s2).chain(s3).chain(s4))
+    }
+}
+
+impl<
+    T1: Shrink + Clone,
+    T2: Shrink + Clone,
+    T3: Shrink + Clone,
+    T4: Shrink + Clone,
+    T5: Shrink + Clone,
+> Shrink for (T1, T2, T3, T4, T5)
+{
+    fn shrink(&self) -> Box<dyn Iterator<Item = (T1, T2, T3, T4, T5)> + '_> {
+        let (a, b, c, d, e) = self;
+        let s1 = a.shrink().map(move |x| (x, b.clone(), c.clone(), d.clone(), e.clone()));
+        let s2 = b.shrink().map(move |x| (a.clone(), x, c.clone(), d.clone(), e.clone()));
+        let s3 = c.shrink().map(move |x| (a.clone(), b.clone(), x, d.clone(), e.clone()));
+        let s4 = d.shrink().map(move |x| (a.clone(), b.clone(), c.clone(), x, e.clone()));
+        let s5 = e.shrink().map(move |x| (a.clone(), b.clone(), c.clone(), d.clone(), x));
+        Box::new(s1.chain(s2).chain(s3).chain(s4).chain(s5))
+    }
+}
+
+/// Picks an index into `weights` with probability proportional to its weight.
+///
+/// Panics if all weights are zero.
+pub fn weighted_index<R: Rng>(rng: &mut R, weights: &[u32]) -> usize {
+    let total: u32 = weights.iter().sum();
+    let mut choice = rng.random_range(0..total);
+    for (i, weight) in weights.iter().enumerate() {
+        if choice < *weight {
+            return i;
+        }
+        choice -= weight;
+    }
+    unreachable!("the choice is below the total weight")
+}
+
+/// Reports that `input` failed the `test`th test, and greedily shrinks it, moving to the first
+/// candidate that still `fails` until no candidate does or `config.max_shrinks` steps were taken.
+/// Returns the result with the step count.
+pub(crate) fn shrink_failure<T: Shrink + Debug>(
+    config: &Config,
+    test: u64,
+    input: T,
+    mut fails: impl FnMut(&T) -> bool,
+) -> (T, u64) {
+    config.reporter.report(&Event::Failed { test, input: &input });
+    let mut current = input;
+    let mut steps = 0;
+    while steps < config.max_shrinks {
+        let Some(smaller) = current.shrink().find(|candidate| fails(candidate)) else {
+            break;
+        };
+        current = smaller;
+        steps += 1;
+        config.reporter.report(&Event::Shrunk { step: steps, input: &current });
+    }
+    (current, steps)
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum ResultStatus {
     /// Exceeds the maximum number of passed tests.
@@ -186,10 +1050,14 @@ pub enum ResultStatus {
     GaveUp,
     /// Exceeded maximum time limit.
     TimedOut,
-    /// The test failed with a counterexample.
-    Failed { arguments: Vec<String> },
-    /// The test was aborted due to an internal error.
+    /// The test failed with a counterexample, possibly by panicking. The message is the one
+    /// the property failed with, such as the error of a `Result`.
+    Failed { arguments: Vec<String>, message: Option<String>, panic: Option<Panic> },
+    /// The test was aborted due to an internal error, such as a panicking generator.
     Aborted { error: String },
+    /// A coverage requirement declared with `cover` was refuted: `label` was attached to
+    /// `observed` percent of the passing tests, short of the `required` percent.
+    InsufficientCoverage { label: String, required: f64, observed: f64 },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -200,6 +1068,61 @@ pub struct RunResult {
     pub passed: u64,
     /// The number of tests that were discarded.
     pub discarded: u64,
+    /// The number of successful shrinking steps applied to the counterexample.
+    pub shrinks: u64,
+    /// The seed of the run, which reproduces it when passed to `Config::with_seed`.
+    pub seed: u64,
+    /// The failing test case, which reruns it alone when passed to `Config::with_replay`.
+    pub replay: Option<Replay>,
+    /// The labels and tabulated values recorded by the passing tests.
+    pub statistics: Statistics,
+    /// The seed pool of a property targeting or fuzzing loop as the run ended, `None` for the
+    /// other runners.
+    pub pool: Option<PoolStats>,
+    /// The criterion that stopped a property targeting or fuzzing loop, `None` for the other
+    /// runners.
+    pub stop: Option<Stop>,
+}
+
+impl fmt::Display for RunResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (passed, discarded) = (self.passed, self.discarded);
+        match &self.status {
+            ResultStatus::Finished => write!(f, "OK, passed {} tests", passed)?,
+            ResultStatus::GaveUp => {
+                write!(f, "Gave up after {} tests and {} discarded tests", passed, discarded)?
+            },
+            ResultStatus::TimedOut => write!(f, "Timed out after {} tests", passed)?,
+            ResultStatus::Failed { arguments, message, panic } => {
+                write!(f, "Failed after {} tests and {} shrinks:", passed, self.shrinks)?;
+                for argument in arguments {
+                    write!(f, "\n    {}", argument)?;
+                }
+                if let Some(message) = message {
+                    write!(f, "\n{}", message)?;
+                }
+                if let Some(panic) = panic {
+                    write!(f, "\n{}", panic)?;
+                }
+            },
+            ResultStatus::Aborted { error } => {
+                write!(f, "Aborted after {} tests: {}", passed, error)?
+            },
+            ResultStatus::InsufficientCoverage { label, required, observed } => {
+                write!(
+                    f,
+                    "Insufficient coverage after {} tests: only {:.1}% {}, but expected {:.1}%",
+                    passed, observed, label, required
+                )?
+            },
+        }
+        match self.replay {
+            Some(replay) => {
+                write!(f, "\nReplay with `Config::with_replay({:?})` (seed {})", replay, self.seed)
+            },
+            None => write!(f, "\nRerun with `Config::with_seed({})`", self.seed),
+        }
+    }
 }
 
 pub trait Implies<T> {
@@ -236,28 +1159,263 @@ impl Implies<Option<bool>> for Option<bool> {
     }
 }
 
-pub fn quickcheck<T: Arbitrary<ThreadRng> + Clone + Debug>(f: fn(T) -> Option<bool>) -> RunResult {
-    let mut rng = rand::rng();
-    let n = 20_000;
+pub fn quickcheck<T: Arbitrary<TestRng> + Shrink + Clone + Debug, P: Testable>(
+    f: impl Property<T, P>,
+) -> RunResult {
+    quickcheck_with(&Config::default(), f)
+}
+
+pub fn quickcheck_with<T: Arbitrary<TestRng> + Shrink + Clone + Debug, P: Testable>(
+    config: &Config,
+    f: impl Property<T, P>,
+) -> RunResult {
+    run::<TestRng, _, _>(config, f, Codec::replays())
+}
+
+/// Like `quickcheck_with`, but every test case is generated by the seedable RNG `R` instead of
+/// `TestRng`, as in `quickcheck_with_rng::<StdRng, _, _>(&config, property)`. Cases are still
+/// seeded from the seed of the run, and replayed with `R`.
+pub fn quickcheck_with_rng<
+    R: Rng + SeedableRng,
+    T: Arbitrary<R> + Shrink + Clone + Debug,
+    P: Testable,
+>(
+    config: &Config,
+    f: impl Property<T, P>,
+) -> RunResult {
+    run::<R, _, _>(config, f, Codec::replays())
+}
+
+/// Like `quickcheck`, but failures are stored in `Config::regressions` as serialized
+/// counterexamples rather than as the test cases they were found in, so they are still checked
+/// after their generator changes.
+pub fn quickcheck_serialized<
+    T: Arbitrary<TestRng> + Shrink + Clone + Debug + Serialize + DeserializeOwned,
+    P: Testable,
+>(
+    f: impl Property<T, P>,
+) -> RunResult {
+    quickcheck_serialized_with(&Config::default(), f)
+}
+
+pub fn quickcheck_serialized_with<
+    T: Arbitrary<TestRng> + Shrink + Clone + Debug + Serialize + DeserializeOwned,
+    P: Testable,
+>(
+    config: &Config,
+    f: impl Property<T, P>,
+) -> RunResult {
+    run::<TestRng, _, _>(config, f, Codec::serde())
+}
+
+/// Shrinks the `input` failing the `test`th test, and stores it in the regression file of the run,
+/// serialized if `codec` allows it, or as the `replay` of the test case it was found in.
+fn counterexample<T: Shrink + Clone + Debug, P: Testable>(
+    config: &Config,
+    f: &mut impl Property<T, P>,
+    codec: &Codec<T>,
+    test: u64,
+    input: T,
+    replay: Option<Replay>,
+) -> (ResultStatus, u64) {
+    let (input, shrinks) =
+        shrink_failure(config, test, input, |candidate| fails(&check(&mut *f, candidate.clone())));
+    let regression = match (codec.encode)(&input) {
+        Some(input) => Some(Regression::Input { input }),
+        None => replay.map(Regression::Replay),
+    };
+    if let (Some(path), Some(regression)) = (&config.regressions, regression) {
+        if let Err(error) = regressions::store(path, &regression) {
+            config.reporter.report(&Event::NotStored { path, error: &error });
+        }
+    }
+    let checked = check(f, input.clone());
+    (failure(&input, checked), shrinks)
+}
+
+fn run<R: Rng + SeedableRng, T: Arbitrary<R> + Shrink + Clone + Debug, P: Testable>(
+    config: &Config,
+    mut f: impl Property<T, P>,
+    codec: Codec<T>,
+) -> RunResult {
+    let seed = config.run_seed();
+    // Past failures are checked before any new test, unless a single case is replayed
+    let regressions = match (&config.regressions, config.replay) {
+        (Some(path), None) => regressions::load(path),
+        _ => vec![],
+    };
+    for regression in regressions {
+        let (input, replay) = match regression {
+            Regression::Input { input } => {
+                match (codec.decode)(input) {
+                    Some(input) => (input, None),
+                    // Counterexamples of another type, from before the property changed
+                    None => continue,
+                }
+            },
+            Regression::Replay(replay) => {
+                match catch(|| replay.generate_with::<R, T>()) {
+                    Ok(input) => (input, Some(replay)),
+                    Err(panic) => {
+                        return report(
+                            config,
+                            RunResult {
+                                passed: 0,
+                                discarded: 0,
+                                shrinks: 0,
+                                seed,
+                                replay: Some(replay),
+                                status: ResultStatus::Aborted {
+                                    error: format!("generator {}", panic),
+                                },
+                                statistics: Statistics::default(),
+                                pool: None,
+                                stop: None,
+                            },
+                        );
+                    },
+                }
+            },
+        };
+        if fails(&check(&mut f, input.clone())) {
+            let (status, shrinks) = counterexample(config, &mut f, &codec, 0, input, replay);
+            return report(
+                config,
+                RunResult {
+                    status,
+                    passed: 0,
+                    discarded: 0,
+                    shrinks,
+                    seed,
+                    replay,
+                    statistics: Statistics::default(),
+                    pool: None,
+                    stop: None,
+                },
+            );
+        }
+    }
+
     let mut passed = 0;
     let mut discarded = 0;
-    for i in 0..n {
-        let input = T::generate(&mut rng, ((i + 1) as f32).log2() as usize);
-        tracing::trace!("test #{}: {:?}", i + 1, input);
-        match f(input.clone()) {
-            None => discarded += 1,
-            Some(true) => passed += 1,
-            Some(false) => {
-                return RunResult {
-                    status: ResultStatus::Failed { arguments: vec![format!("{:?}", input)] },
+    let mut statistics = Statistics::default();
+    loop {
+        // Coverage requirements keep the run going until they are confirmed or refuted
+        if passed >= config.max_tests {
+            match statistics.check_coverage() {
+                Coverage::Sufficient => break,
+                Coverage::Undecided => {},
+                Coverage::Insufficient { label, required, observed } => {
+                    return report(
+                        config,
+                        RunResult {
+                            passed,
+                            discarded,
+                            shrinks: 0,
+                            seed,
+                            replay: None,
+                            status: ResultStatus::InsufficientCoverage {
+                                label,
+                                required,
+                                observed,
+                            },
+                            statistics,
+                            pool: None,
+                            stop: None,
+                        },
+                    );
+                },
+            }
+        }
+        if discarded > config.max_discards() {
+            return report(
+                config,
+                RunResult {
                     passed,
                     discarded,
-                };
+                    shrinks: 0,
+                    seed,
+                    replay: None,
+                    status: ResultStatus::GaveUp,
+                    statistics,
+                    pool: None,
+                    stop: None,
+                },
+            );
+        }
+        let i = passed + discarded + 1;
+        let case = config.case(seed, i);
+        let input: T = match catch(|| case.generate_with::<R, T>()) {
+            Ok(input) => input,
+            Err(panic) => {
+                return report(
+                    config,
+                    RunResult {
+                        passed,
+                        discarded,
+                        shrinks: 0,
+                        seed,
+                        replay: Some(case),
+                        status: ResultStatus::Aborted { error: format!("generator {}", panic) },
+                        statistics,
+                        pool: None,
+                        stop: None,
+                    },
+                );
+            },
+        };
+        tracing::trace!("test #{}: {:?}", i, input);
+        config.reporter.report(&Event::Started { test: i, input: &input });
+        let (checked, labels) = record(|| check(&mut f, input.clone()));
+        match checked {
+            Ok(TestResult::Discarded) => {
+                discarded += 1;
+                config.reporter.report(&Event::Discarded { test: i });
+            },
+            Ok(TestResult::Passed) => {
+                passed += 1;
+                statistics.add(labels);
+                config.reporter.report(&Event::Passed { test: i });
             },
+            Ok(TestResult::Failed { .. }) | Err(_) => {
+                let (status, shrinks) =
+                    counterexample(config, &mut f, &codec, i, input, Some(case));
+                return report(
+                    config,
+                    RunResult {
+                        status,
+                        passed,
+                        discarded,
+                        shrinks,
+                        seed,
+                        replay: Some(case),
+                        statistics,
+                        pool: None,
+                        stop: None,
+                    },
+                );
+            },
+        }
+        // A replayed run consists of the replayed case only
+        if config.replay.is_some() {
+            break;
         }
     }
 
-    RunResult { passed, discarded, status: ResultStatus::Finished }
+    report(
+        config,
+        RunResult {
+            passed,
+            discarded,
+            shrinks: 0,
+            seed,
+            replay: None,
+            status: ResultStatus::Finished,
+            statistics,
+            pool: None,
+            stop: None,
+        },
+    )
 }
 
 #[cfg(test)]
@@ -285,7 +1443,7 @@ mod tests {
             Some(copy == *x)
         });
         assert!(result.passed < 100);
-        assert!(result.status == ResultStatus::Failed { arguments: vec![format!("{:?}", result)] });
+        assert!(matches!(result.status, ResultStatus::Failed { .. }));
     }
 
     #[test]
@@ -299,4 +1457,306 @@ mod tests {
         assert_eq!(result.discarded, 0);
         assert!(result.status == ResultStatus::Finished);
     }
+
+    #[test]
+    fn test_quickcheck_shrinks() {
+        let result = quickcheck(|x: Vec<i32>| Some(x.len() < 3));
+        let arguments = vec!["[0, 0, 0]".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+        assert!(result.shrinks > 0);
+    }
+
+    #[test]
+    fn test_quickcheck_panic() {
+        let result = quickcheck(|x: Vec<i32>| Some(x[1..].is_empty()));
+        let ResultStatus::Failed { arguments, panic: Some(panic), .. } = result.status else {
+            panic!("expected a panicking failure, got {:?}", result.status);
+        };
+        assert_eq!(arguments, vec!["[]".to_string()]);
+        assert!(panic.message.contains("out of range"));
+        assert!(panic.location.is_some());
+    }
+
+    #[test]
+    fn test_quickcheck_generator_panic() {
+        #[derive(Clone, Debug)]
+        struct Unlucky;
+
+        impl<R: Rng> Arbitrary<R> for Unlucky {
+            fn generate(_rng: &mut R, _n: usize) -> Unlucky {
+                panic!("no luck")
+            }
+        }
+
+        impl Shrink for Unlucky {}
+
+        let result = quickcheck(|_: Unlucky| Some(true));
+        assert!(
+            matches!(result.status, ResultStatus::Aborted { error } if error.contains("no luck"))
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[derive(Clone, Debug, PartialEq, Arbitrary, Mutate, Shrink, Crossover)]
+    enum Expr {
+        #[crabcheck(weight = 3)]
+        Lit(i32),
+        Neg(Box<Expr>),
+        Add {
+            left: Box<Expr>,
+            right: Box<Expr>,
+        },
+    }
+
+    #[cfg(feature = "derive")]
+    impl Expr {
+        fn depth(&self) -> usize {
+            match self {
+                Expr::Lit(_) => 1,
+                Expr::Neg(e) => 1 + e.depth(),
+                Expr::Add { left, right } => 1 + left.depth().max(right.depth()),
+            }
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_depth_is_bounded_by_size() {
+        let mut rng = TestRng::seed_from_u64(0);
+        for n in 0..20 {
+            let expr = Expr::generate(&mut rng, n);
+            assert!(expr.depth() <= n + 1);
+            assert!(expr.mutate(&mut rng, n).depth() <= n + 2);
+        }
+        assert!(matches!(Expr::generate(&mut rng, 0), Expr::Lit(_)));
+    }
+
+    #[cfg(feature = "derive")]
+    #[crabcheck::property]
+    fn prop_double_reverse(xs: Vec<i32>) -> bool {
+        let mut copy = xs.clone();
+        copy.reverse();
+        copy.reverse();
+        copy == xs
+    }
+
+    #[cfg(feature = "derive")]
+    #[crabcheck::property(config = Config::new()
+        .with_max_tests(10)
+        .with_regressions(std::env::temp_dir().join("crabcheck-prop_push_is_noop.jsonl")))]
+    #[should_panic(expected = "Failed after")]
+    fn prop_push_is_noop(mut xs: Vec<u8>, x: u8) -> Result<(), String> {
+        let before = xs.len();
+        xs.push(x);
+        if xs.len() == before { Ok(()) } else { Err(format!("{:?} grew", xs)) }
+    }
+
+    #[cfg(feature = "derive")]
+    fn quickcheck_once<T: Arbitrary<TestRng> + Shrink + Clone + Debug, P: Testable>(
+        config: &Config,
+        f: impl Property<T, P>,
+    ) -> RunResult {
+        quickcheck_with(&config.clone().with_max_tests(1), f)
+    }
+
+    #[cfg(feature = "derive")]
+    #[crabcheck::property(runner = quickcheck_once)]
+    fn prop_runs_once(_: bool, _: Option<bool>, expr: Expr) {
+        static RUNS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        assert!(expr.depth() > 0);
+        assert_eq!(RUNS.fetch_add(1, std::sync::atomic::Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_run_result_display() {
+        let result = quickcheck_with(&Config::new().with_seed(1), |x: u8| x < 3);
+        let display = result.to_string();
+        assert!(display.starts_with(&format!("Failed after {} tests and ", result.passed)));
+        assert!(display.contains("\n    3\nReplay with `Config::with_replay(Replay { seed: "));
+        assert_eq!(
+            quickcheck_with(&Config::new().with_seed(1), |_: u8| true).to_string(),
+            "OK, passed 100 tests\nRerun with `Config::with_seed(1)`"
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_shrinks_counterexample() {
+        let result = quickcheck(|e: Expr| Some(!matches!(e, Expr::Neg(_))));
+        let arguments = vec!["Neg(Lit(0))".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_crossover() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let add = |left, right| Expr::Add { left: Box::new(left), right: Box::new(right) };
+        let first = add(Expr::Lit(1), Expr::Lit(2));
+        let second = add(Expr::Lit(3), Expr::Lit(4));
+        let children: Vec<Expr> = (0..100).map(|_| first.crossover(&second, &mut rng)).collect();
+        assert!(children.contains(&add(Expr::Lit(1), Expr::Lit(4))));
+        assert!(children.contains(&add(Expr::Lit(3), Expr::Lit(2))));
+
+        // Different variants can only pick a parent
+        let child = Expr::Lit(1).crossover(&first, &mut rng);
+        assert!(child == Expr::Lit(1) || child == first);
+    }
+
+    #[test]
+    fn test_vec_crossover() {
+        let mut rng = TestRng::seed_from_u64(0);
+        for _ in 0..100 {
+            // A prefix of ones followed by a suffix of twos
+            let child = vec![1; 5].crossover(&vec![2; 8], &mut rng);
+            assert!(child.is_sorted());
+            assert!(child.len() >= 5 && child.len() <= 8);
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive_struct() {
+        #[derive(Clone, Debug, Arbitrary, Mutate, Shrink)]
+        struct Pair<T> {
+            first: T,
+            rest: Vec<T>,
+        }
+
+        let result = quickcheck(|p: Pair<usize>| Some(p.rest.len() <= p.first));
+        let arguments = vec!["Pair { first: 0, rest: [0] }".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+    }
+
+    #[test]
+    fn test_numeric_generation_is_bounded_by_size() {
+        let mut rng = TestRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let x = i16::generate(&mut rng, 5);
+            let y = u16::generate(&mut rng, 5);
+            let z = f64::generate(&mut rng, 5);
+            assert!(y <= 5 || y == u16::MAX);
+            assert!(z.abs() <= 5.0 || !z.is_finite() || z.abs() == f64::MAX);
+            assert!(x == i16::MIN || x == i16::MAX || x.abs() <= 5);
+        }
+    }
+
+    #[test]
+    fn test_numeric_mutation_does_not_overflow() {
+        let mut rng = TestRng::seed_from_u64(0);
+        for _ in 0..100 {
+            assert!(i32::MAX.mutate(&mut rng, 10) >= i32::MAX - 10);
+            assert!(i64::MIN.mutate(&mut rng, 10) <= i64::MIN + 10);
+            assert!(u128::MAX.mutate(&mut rng, 10) >= u128::MAX - 10);
+            assert!(isize::MIN.mutate(&mut rng, 10) <= isize::MIN + 10);
+            assert!(f32::MAX.mutate(&mut rng, 10).is_finite());
+        }
+    }
+
+    #[test]
+    fn test_shrink_i32() {
+        assert_eq!(100.shrink().collect::<Vec<_>>(), vec![0, 50, 75, 88, 94, 97, 99]);
+        assert_eq!((-3).shrink().collect::<Vec<_>>(), vec![0, 3, -2]);
+    }
+
+    #[test]
+    fn test_quickcheck_collections_shrink() {
+        let result = quickcheck(|x: Option<u8>| Some(x.is_none()));
+        let arguments = vec!["Some(0)".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+
+        let result = quickcheck(|map: BTreeMap<u8, Vec<u8>>| Some(map.len() < 2));
+        let arguments = vec!["{0: [], 1: []}".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+    }
+
+    #[test]
+    fn test_collection_mutation() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let mut set = HashSet::<u8>::new();
+        let mut sizes = HashSet::new();
+        for _ in 0..100 {
+            set = set.mutate(&mut rng, 10);
+            sizes.insert(set.len());
+        }
+        assert!(sizes.len() > 1);
+
+        // Hashed collections with different random states mutate alike from the same seed
+        let map: HashMap<u8, u8> = (0..50).map(|i| (i, i)).collect();
+        for seed in 0..20 {
+            let mutate = |set: &HashSet<u8>| set.mutate(&mut TestRng::seed_from_u64(seed), 10);
+            assert_eq!(mutate(&set), mutate(&set.iter().copied().collect()));
+            let mutate = |map: &HashMap<u8, u8>| map.mutate(&mut TestRng::seed_from_u64(seed), 10);
+            assert_eq!(mutate(&map), mutate(&map.clone().into_iter().collect()));
+        }
+    }
+
+    #[test]
+    fn test_vec_mutation_mix() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let inserting = VecMutation {
+            modify: 0,
+            insert: 1,
+            delete: 0,
+            duplicate: 0,
+            reverse: 0,
+            swap: 0,
+            splice: 0,
+        };
+        let mut list: Vec<u8> = vec![];
+        for i in 1..=20 {
+            list = inserting.mutate(&list, &mut rng, 10);
+            assert_eq!(list.len(), i.min(10));
+        }
+
+        // The default mix changes lengths both ways, but never past the size
+        let lengths: HashSet<usize> = (0..100).map(|_| list.mutate(&mut rng, 12).len()).collect();
+        assert!(lengths.contains(&9) && lengths.contains(&11));
+        assert!(lengths.iter().all(|len| *len <= 12));
+    }
+
+    #[test]
+    fn test_quickcheck_seeded() {
+        let config = Config::new().with_seed(42);
+        let property: fn(Vec<i32>) -> Option<bool> = |x| Some(x.iter().all(|v| *v < 4));
+        let first = quickcheck_with(&config, property);
+        let second = quickcheck_with(&config, property);
+        assert_eq!(first, second);
+        assert_eq!(first.seed, 42);
+    }
+
+    #[test]
+    fn test_quickcheck_replay() {
+        let property: fn(Vec<i32>) -> Option<bool> = |x| Some(x.iter().all(|v| *v < 4));
+        let failure = quickcheck(property);
+        let replay = failure.replay.expect("the property should fail");
+
+        let result = quickcheck_with(&Config::new().with_replay(replay), property);
+        assert_eq!(result.passed, 0);
+        assert_eq!(result.status, failure.status);
+        assert_eq!(result.replay, Some(replay));
+    }
+
+    #[test]
+    fn test_quickcheck_with_rng() {
+        type Rng = rand_chacha::ChaCha20Rng;
+        let property: fn(Vec<i32>) -> Option<bool> = |x| Some(x.iter().all(|v| *v < 4));
+        let config = Config::new().with_seed(7);
+        let failure = quickcheck_with_rng::<Rng, _, _>(&config, property);
+        let replay = failure.replay.expect("the property should fail");
+        assert_eq!(quickcheck_with_rng::<Rng, _, _>(&config, property).replay, Some(replay));
+
+        let result = quickcheck_with_rng::<Rng, _, _>(&config.with_replay(replay), property);
+        assert_eq!(result.passed, 0);
+        assert_eq!(result.status, failure.status);
+    }
+
+    #[test]
+    fn test_quickcheck_gave_up() {
+        let config = Config::new().with_max_tests(10).with_max_discard_ratio(2);
+        let result = quickcheck_with(&config, |x: usize| (x == 12345678).implies(true));
+        assert_eq!(result.passed, 0);
+        assert_eq!(result.discarded, 21);
+        assert!(result.status == ResultStatus::GaveUp);
+    }
 }
@@ -0,0 +1,154 @@
+use {
+    crate::rng::Replay,
+    serde::{
+        Deserialize,
+        Serialize,
+        de::DeserializeOwned,
+    },
+    serde_json::Value,
+    std::{
+        fs,
+        io::{
+            self,
+            Write,
+        },
+        path::{
+            Path,
+            PathBuf,
+        },
+    },
+};
+
+/// The first line of every regression file.
+const HEADER: &str = "# Failures found by crabcheck, checked before any new test. Commit this file \
+                      to keep checking them.";
+
+/// A past failure stored in a regression file, one JSON object per line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Regression {
+    /// The serialized counterexample, which survives changes to its generator.
+    Input { input: Value },
+    /// The test case the failure was found in, regenerated from its seed and size.
+    Replay(Replay),
+}
+
+/// How the inputs of a property are written to and read from its regression file. Inputs that
+/// cannot be serialized are stored as the `Replay` of the test case they were found in.
+pub(crate) struct Codec<T> {
+    pub(crate) encode: fn(&T) -> Option<Value>,
+    pub(crate) decode: fn(Value) -> Option<T>,
+}
+
+impl<T> Codec<T> {
+    pub(crate) fn replays() -> Codec<T> {
+        Codec { encode: |_| None, decode: |_| None }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Codec<T> {
+    pub(crate) fn serde() -> Codec<T> {
+        Codec {
+            encode: |input| serde_json::to_value(input).ok(),
+            decode: |value| serde_json::from_value(value).ok(),
+        }
+    }
+}
+
+/// The regression file of the property `name` defined in `module_path`, under the
+/// `crabcheck-regressions` directory of the crate at `manifest_dir`. This is where
+/// `#[crabcheck::property]` tests store their failures.
+pub fn path(manifest_dir: &str, module_path: &str, name: &str) -> PathBuf {
+    let mut path = Path::new(manifest_dir).join("crabcheck-regressions");
+    path.extend(module_path.split("::"));
+    path.join(format!("{}.jsonl", name))
+}
+
+/// The regressions stored at `path`. A missing file holds none, and lines that do not parse,
+/// such as comments, are skipped.
+pub fn load(path: &Path) -> Vec<Regression> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return vec![];
+    };
+    contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Appends `regression` to the file at `path` unless it is already stored there, creating the
+/// file and its directories if needed.
+pub fn store(path: &Path, regression: &Regression) -> io::Result<()> {
+    if load(path).contains(regression) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let exists = path.exists();
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if !exists {
+        writeln!(file, "{}", HEADER)?;
+    }
+    writeln!(file, "{}", serde_json::to_string(regression)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            config::Config,
+            quickcheck::{
+                ResultStatus,
+                quickcheck_serialized_with,
+                quickcheck_with,
+            },
+        },
+    };
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("crabcheck-{}-{}.jsonl", name, rand::random::<u64>()))
+    }
+
+    #[test]
+    fn test_regressions_are_replayed() {
+        let path = temp_path("replays");
+        let config = Config::new().with_seed(1).with_regressions(&path);
+        let first = quickcheck_with(&config, |x: u8| x < 3);
+        let replay = first.replay.unwrap();
+        assert_eq!(load(&path), vec![Regression::Replay(replay)]);
+
+        // The stored case fails again before any new test, and is not stored twice
+        let second = quickcheck_with(&config.clone().with_max_tests(0), |x: u8| x < 3);
+        assert_eq!((second.status, second.replay), (first.status, Some(replay)));
+        assert_eq!(load(&path).len(), 1);
+
+        // Fixed failures keep being checked
+        let fixed = quickcheck_with(&config.with_max_tests(0), |_: u8| true);
+        assert_eq!(fixed.status, ResultStatus::Finished);
+        assert!(fs::read_to_string(&path).unwrap().starts_with(HEADER));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_serialized_regressions() {
+        let path = temp_path("inputs");
+        let config = Config::new().with_seed(1).with_regressions(&path);
+        quickcheck_serialized_with(&config, |x: Vec<u8>| x.len() < 3);
+        assert_eq!(load(&path), vec![Regression::Input { input: serde_json::json!([0, 0, 0]) }]);
+
+        let result =
+            quickcheck_serialized_with(&config.with_max_tests(0), |x: Vec<u8>| x.len() < 3);
+        let arguments = vec!["[0, 0, 0]".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+        assert_eq!(result.replay, None);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_regression_path() {
+        let path = path("/crate", "crate::module::tests", "prop_name");
+        assert_eq!(
+            path,
+            Path::new("/crate/crabcheck-regressions/crate/module/tests/prop_name.jsonl")
+        );
+    }
+}
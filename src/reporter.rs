@@ -0,0 +1,360 @@
+use {
+    crate::{
+        config::Config,
+        quickcheck::{
+            ResultStatus,
+            RunResult,
+        },
+        statistics::Coverage,
+        stopping::Stop,
+    },
+    serde_json::{
+        Value,
+        json,
+    },
+    std::{
+        fmt::{
+            self,
+            Debug,
+        },
+        io::{
+            self,
+            Write,
+        },
+        path::Path,
+        sync::Mutex,
+    },
+};
+
+/// Something that happened during a run, told to the `Reporter` of its configuration. Tests (or
+/// loop iterations) are numbered from 1, and inputs and feedback are only formatted by the
+/// reporters that show them.
+#[derive(Clone, Copy, Debug)]
+pub enum Event<'a> {
+    /// A test is about to run on `input`.
+    Started {
+        test: u64,
+        input: &'a dyn Debug,
+    },
+    Passed {
+        test: u64,
+    },
+    Discarded {
+        test: u64,
+    },
+    /// A targeting or fuzzing loop found a seed better than all before it, or a seed joining the
+    /// Pareto front of a multi-objective loop.
+    NewBest {
+        test: u64,
+        input: &'a dyn Debug,
+        feedback: &'a dyn Debug,
+        pool_size: usize,
+    },
+    /// The property failed on `input`, which is about to be shrunk. Failing regressions stored by
+    /// an earlier run are reported as test 0.
+    Failed {
+        test: u64,
+        input: &'a dyn Debug,
+    },
+    /// The `step`th shrinking step found the smaller counterexample `input`.
+    Shrunk {
+        step: u64,
+        input: &'a dyn Debug,
+    },
+    /// The counterexample could not be appended to the regression file at `path`.
+    NotStored {
+        path: &'a Path,
+        error: &'a io::Error,
+    },
+    /// The run is over after `tests` tests, with `result` unless it is an optimizing loop, which
+    /// returns its best seed instead. Targeting and fuzzing loops tell which criterion stopped
+    /// them.
+    Finished {
+        tests: u64,
+        stop: Option<Stop>,
+        result: Option<&'a RunResult>,
+    },
+}
+
+impl Event<'_> {
+    /// The event as a JSON object, as written by `JsonLines`.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Event::Started { test, input } => {
+                json!({ "event": "started", "test": test, "input": format!("{:?}", input) })
+            },
+            Event::Passed { test } => json!({ "event": "passed", "test": test }),
+            Event::Discarded { test } => json!({ "event": "discarded", "test": test }),
+            Event::NewBest { test, input, feedback, pool_size } => {
+                json!({
+                    "event": "new_best",
+                    "test": test,
+                    "input": format!("{:?}", input),
+                    "feedback": format!("{:?}", feedback),
+                    "pool_size": pool_size,
+                })
+            },
+            Event::Failed { test, input } => {
+                json!({ "event": "failed", "test": test, "input": format!("{:?}", input) })
+            },
+            Event::Shrunk { step, input } => {
+                json!({ "event": "shrunk", "step": step, "input": format!("{:?}", input) })
+            },
+            Event::NotStored { path, error } => {
+                json!({
+                    "event": "not_stored",
+                    "path": path.display().to_string(),
+                    "error": error.to_string(),
+                })
+            },
+            Event::Finished { tests, stop, result: None } => {
+                json!({
+                    "event": "finished",
+                    "tests": tests,
+                    "stop": stop.map(|stop| format!("{:?}", stop)),
+                })
+            },
+            Event::Finished { tests, stop, result: Some(result) } => {
+                json!({
+                    "event": "finished",
+                    "tests": tests,
+                    "stop": stop.map(|stop| format!("{:?}", stop)),
+                    "status": status(&result.status),
+                    "passed": result.passed,
+                    "discarded": result.discarded,
+                    "shrinks": result.shrinks,
+                    "seed": result.seed,
+                    "summary": result.to_string(),
+                    "statistics": result.statistics.to_string(),
+                })
+            },
+        }
+    }
+}
+
+fn status(status: &ResultStatus) -> &'static str {
+    match status {
+        ResultStatus::Finished => "finished",
+        ResultStatus::GaveUp => "gave_up",
+        ResultStatus::TimedOut => "timed_out",
+        ResultStatus::Failed { .. } => "failed",
+        ResultStatus::Aborted { .. } => "aborted",
+        ResultStatus::InsufficientCoverage { .. } => "insufficient_coverage",
+    }
+}
+
+/// Observes the events of the runs configured with it. Parallel runs report from several
+/// threads.
+pub trait Reporter: Debug + Send + Sync {
+    fn report(&self, event: &Event);
+}
+
+/// Reports nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quiet;
+
+impl Reporter for Quiet {
+    fn report(&self, _: &Event) {}
+}
+
+/// Prints the new best seeds that targeting and fuzzing loops find after a power of two tests, the
+/// last one when they finish and what stopped them early, the counterexamples that could not be
+/// stored, and the statistics of finished runs if they recorded any.
+#[derive(Debug, Default)]
+pub struct Human {
+    /// The last new best seed, unless it was printed.
+    pending: Mutex<Option<String>>,
+}
+
+impl Reporter for Human {
+    fn report(&self, event: &Event) {
+        let mut pending = self.pending.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match event {
+            Event::NewBest { test, input, feedback, pool_size } => {
+                let line = format!(
+                    "New best after {} tests (pool size {}): {:?} from {:?}",
+                    test, pool_size, feedback, input
+                );
+                if test.is_power_of_two() {
+                    println!("{}", line);
+                    *pending = None;
+                } else {
+                    *pending = Some(line);
+                }
+            },
+            Event::NotStored { path, error } => {
+                eprintln!("Could not store the counterexample in {}: {}", path.display(), error)
+            },
+            Event::Finished { tests, stop, result } => {
+                if let Some(line) = pending.take() {
+                    println!("{}", line);
+                }
+                if let Some(stop) = stop.filter(|stop| *stop != Stop::Budget) {
+                    println!("Stopped after {} tests: {}", tests, stop);
+                }
+                if let Some(result) = result.filter(|result| !result.statistics.is_empty()) {
+                    println!("{}", result.statistics);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Writes every event as a line of JSON, as given by `Event::to_json`.
+pub struct JsonLines {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLines {
+    pub fn new(writer: impl Write + Send + 'static) -> JsonLines {
+        JsonLines { writer: Mutex::new(Box::new(writer)) }
+    }
+
+    pub fn stdout() -> JsonLines {
+        JsonLines::new(io::stdout())
+    }
+}
+
+impl Debug for JsonLines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLines").finish_non_exhaustive()
+    }
+}
+
+impl Reporter for JsonLines {
+    fn report(&self, event: &Event) {
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Reporting never fails a run
+        let _ = writeln!(writer, "{}", event.to_json());
+    }
+}
+
+/// Fails a run that finished all its tests if its statistics refute a coverage requirement, for
+/// the runners that do not check coverage as they go.
+fn check_coverage(result: &mut RunResult) {
+    if result.status != ResultStatus::Finished {
+        return;
+    }
+    if let Coverage::Insufficient { label, required, observed } = result.statistics.check_coverage()
+    {
+        result.status = ResultStatus::InsufficientCoverage { label, required, observed };
+    }
+}
+
+/// Reports a finished run to the reporter of `config`.
+pub(crate) fn report(config: &Config, mut result: RunResult) -> RunResult {
+    check_coverage(&mut result);
+    let tests = result.passed + result.discarded;
+    config.reporter.report(&Event::Finished { tests, stop: None, result: Some(&result) });
+    result
+}
+
+/// Reports a loop run to completion, stopped by `stop`: a time limit makes it time out.
+pub(crate) fn report_stop(config: &Config, mut result: RunResult, stop: Stop) -> RunResult {
+    result.stop = Some(stop);
+    if matches!(stop, Stop::Time | Stop::CpuTime) {
+        result.status = ResultStatus::TimedOut;
+    }
+    check_coverage(&mut result);
+    let tests = result.passed + result.discarded;
+    config.reporter.report(&Event::Finished { tests, stop: Some(stop), result: Some(&result) });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::quickcheck::{
+            Implies,
+            quickcheck_with,
+        },
+        std::sync::Arc,
+    };
+
+    /// Keeps the names of the events it is told about.
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Reporter for Arc<Recorder> {
+        fn report(&self, event: &Event) {
+            self.0.lock().unwrap().push(event.to_json()["event"].as_str().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn test_events() {
+        let recorder = Arc::new(Recorder::default());
+        let config = Config::default().with_seed(1).with_reporter(recorder.clone());
+        let result = quickcheck_with(&config, |x: u8| (x % 2 == 1).implies(x < 3));
+
+        let events = recorder.0.lock().unwrap();
+        let count = |name: &str| events.iter().filter(|event| *event == name).count() as u64;
+        assert_eq!(count("passed"), result.passed);
+        assert_eq!(count("discarded"), result.discarded);
+        assert_eq!(count("started"), result.passed + result.discarded + 1);
+        assert_eq!((count("failed"), count("shrunk")), (1, result.shrinks));
+        assert_eq!(events.last().unwrap(), "finished");
+    }
+
+    #[test]
+    fn test_human_throttles_new_bests() {
+        let human = Human::default();
+        let new_best = |test| Event::NewBest { test, input: &0, feedback: &0, pool_size: 1 };
+
+        // Only the new bests after a power of two tests are printed as they are found
+        human.report(&new_best(3));
+        assert!(human.pending.lock().unwrap().is_some());
+        human.report(&new_best(4));
+        assert!(human.pending.lock().unwrap().is_none());
+
+        // The last one is printed when the loop finishes
+        human.report(&new_best(5));
+        human.report(&Event::Finished { tests: 10, stop: Some(Stop::Budget), result: None });
+        assert!(human.pending.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_not_stored() {
+        let recorder = Arc::new(Recorder::default());
+        // A file cannot be created below another file
+        let config = Config::default()
+            .with_seed(1)
+            .with_reporter(recorder.clone())
+            .with_regressions(Path::new(file!()).join("regressions.jsonl"));
+        quickcheck_with(&config, |x: u8| x < 3);
+
+        let events = recorder.0.lock().unwrap();
+        assert!(events.iter().any(|event| event == "not_stored"));
+    }
+
+    /// A writer whose output can be read back.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let buffer = Buffer::default();
+        let config = Config::default().with_seed(0).with_reporter(JsonLines::new(buffer.clone()));
+        quickcheck_with(&config, |x: Vec<u8>| x.len() < 1000);
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<Value> =
+            output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 201);
+        assert_eq!(events[0]["event"], "started");
+        assert_eq!(events[200]["status"], "finished");
+        assert_eq!(events[200]["passed"], 100);
+    }
+}
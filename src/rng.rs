@@ -0,0 +1,54 @@
+use {
+    crate::quickcheck::Arbitrary,
+    rand::{
+        Rng,
+        RngCore,
+        SeedableRng,
+    },
+    rand_chacha::ChaCha8Rng,
+    serde::{
+        Deserialize,
+        Serialize,
+    },
+};
+
+/// The random number generator passed to generators and mutators by every runner, unless
+/// `quickcheck_with_rng` is given another one.
+///
+/// It is seeded from `Config::seed` (or a fresh random seed), so any run can be reproduced.
+pub type TestRng = ChaCha8Rng;
+
+/// Everything needed to regenerate a single test case: the seed of its generator and its size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub size: usize,
+}
+
+impl Replay {
+    pub fn rng(&self) -> TestRng {
+        TestRng::seed_from_u64(self.seed)
+    }
+
+    pub fn generate<T: Arbitrary<TestRng>>(&self) -> T {
+        self.generate_with::<TestRng, T>()
+    }
+
+    /// Regenerates the case with the seedable RNG `R` rather than `TestRng`, for runs made with
+    /// `quickcheck_with_rng`.
+    pub fn generate_with<R: Rng + SeedableRng, T: Arbitrary<R>>(&self) -> T {
+        T::generate(&mut R::seed_from_u64(self.seed), self.size)
+    }
+}
+
+pub(crate) fn random_seed() -> u64 {
+    rand::random()
+}
+
+/// Derives the seed of the `i`th test case of the run seeded with `run_seed`. Each case gets its
+/// own ChaCha stream, so cases are independent of each other and of the order they ran in.
+pub(crate) fn case_seed(run_seed: u64, i: u64) -> u64 {
+    let mut rng = TestRng::seed_from_u64(run_seed);
+    rng.set_stream(i);
+    rng.next_u64()
+}
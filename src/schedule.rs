@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+
+/// Where a seed stands in its pool when it is selected, which a `Schedule` turns into energy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeedStats {
+    /// The number of seeds of the pool with a strictly better feedback, so 0 for the best seeds.
+    pub rank: usize,
+    /// The number of seeds of the pool with the same feedback, including this one.
+    pub frequency: usize,
+    /// The number of seeds added to the pool after this one.
+    pub age: u64,
+    /// The number of times the seed was selected before.
+    pub selected: u64,
+    /// The number of seeds in the pool.
+    pub pool_size: usize,
+}
+
+/// A power schedule, deciding how many consecutive inputs are mutated from a seed each time the
+/// pool selects it.
+///
+/// Pools select the seed picked the fewest times so far, the best one first on ties, so that
+/// every seed gets its turn; schedules decide how long each turn lasts.
+pub trait Schedule: Debug + Send + Sync {
+    /// The energy of a seed being selected, at least 1.
+    fn energy(&self, seed: &SeedStats) -> usize;
+}
+
+/// The same energy for every seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Uniform {
+    pub energy: usize,
+}
+
+impl Default for Uniform {
+    fn default() -> Self {
+        Uniform { energy: 16 }
+    }
+}
+
+impl Schedule for Uniform {
+    fn energy(&self, _: &SeedStats) -> usize {
+        self.energy.max(1)
+    }
+}
+
+/// Energy decreasing linearly with the feedback rank of the seed, from `energy` for the best
+/// seeds down to a fraction of it for the worst.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exploit {
+    pub energy: usize,
+}
+
+impl Default for Exploit {
+    fn default() -> Self {
+        Exploit { energy: 16 }
+    }
+}
+
+impl Schedule for Exploit {
+    fn energy(&self, seed: &SeedStats) -> usize {
+        let size = seed.pool_size.max(1);
+        (self.energy * (size - seed.rank.min(size - 1)) / size).max(1)
+    }
+}
+
+/// Energy decreasing with the age of the seed, favouring the seeds found last.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Explore {
+    pub energy: usize,
+}
+
+impl Default for Explore {
+    fn default() -> Self {
+        Explore { energy: 16 }
+    }
+}
+
+impl Schedule for Explore {
+    fn energy(&self, seed: &SeedStats) -> usize {
+        (self.energy / (1 + seed.age as usize)).max(1)
+    }
+}
+
+/// AFLFast's schedule: energy doubling every time the seed is selected, divided by the number of
+/// seeds sharing its feedback, and capped at `max_energy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fast {
+    pub energy: usize,
+    pub max_energy: usize,
+}
+
+impl Default for Fast {
+    fn default() -> Self {
+        Fast { energy: 4, max_energy: 256 }
+    }
+}
+
+impl Schedule for Fast {
+    fn energy(&self, seed: &SeedStats) -> usize {
+        let doubled = self.energy.saturating_mul(1 << seed.selected.min(usize::BITS as u64 - 1));
+        (doubled / seed.frequency.max(1)).clamp(1, self.max_energy.max(1))
+    }
+}
+
+/// Energy inversely proportional to the number of seeds sharing the feedback of the seed,
+/// favouring rare feedback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rare {
+    pub energy: usize,
+}
+
+impl Default for Rare {
+    fn default() -> Self {
+        Rare { energy: 16 }
+    }
+}
+
+impl Schedule for Rare {
+    fn energy(&self, seed: &SeedStats) -> usize {
+        (self.energy / seed.frequency.max(1)).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedules() {
+        let seed = SeedStats { rank: 0, frequency: 1, age: 0, selected: 0, pool_size: 4 };
+        let worst = SeedStats { rank: 3, ..seed };
+        let old = SeedStats { age: 3, ..seed };
+        let common = SeedStats { frequency: 4, ..seed };
+        let picked = SeedStats { selected: 2, ..seed };
+
+        assert_eq!(Uniform::default().energy(&worst), Uniform::default().energy(&seed));
+        assert_eq!((Exploit::default().energy(&seed), Exploit::default().energy(&worst)), (16, 4));
+        assert_eq!((Explore::default().energy(&seed), Explore::default().energy(&old)), (16, 4));
+        assert_eq!((Rare::default().energy(&seed), Rare::default().energy(&common)), (16, 4));
+        assert_eq!((Fast::default().energy(&seed), Fast::default().energy(&picked)), (4, 16));
+        assert_eq!(Fast::default().energy(&common), 1);
+        assert_eq!(Fast::default().energy(&SeedStats { selected: 100, ..seed }), 256);
+    }
+}
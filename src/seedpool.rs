@@ -1,13 +1,283 @@
+use {
+    crate::{
+        acceptance::{
+            Acceptance,
+            Candidate,
+            Improvement,
+        },
+        config::Config,
+        feedback::{
+            Dominance,
+            Measure,
+        },
+        rng::TestRng,
+        schedule::{
+            Exploit,
+            Schedule,
+            SeedStats,
+        },
+    },
+    std::{
+        any::Any,
+        cmp::{
+            Ordering,
+            Reverse,
+        },
+        collections::{
+            BTreeMap,
+            BTreeSet,
+            HashSet,
+            hash_map::DefaultHasher,
+        },
+        fmt::Debug,
+        hash::{
+            Hash,
+            Hasher,
+        },
+        sync::Arc,
+    },
+};
+
+/// Fingerprints the inputs of the pools of the loops configured with `Config::with_fingerprint`,
+/// which reject inputs fingerprinted like the input of one of their seeds.
+pub type Fingerprint = fn(&dyn Any) -> u64;
+
+/// The fingerprint hashing inputs of type `T`, as set by `Config::with_hashing`.
+pub fn hashed<T: Hash + 'static>(input: &dyn Any) -> u64 {
+    let input = input.downcast_ref::<T>().expect("the fingerprint hashes inputs of another type");
+    hash(input)
+}
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[derive(Clone, Debug)]
-pub struct Seed<T: Clone, F: Clone + Ord> {
+pub struct Seed<T, F> {
     pub input: T,
     pub feedback: F,
+    /// The number of inputs left to mutate from this seed in its current turn.
     pub energy: usize,
+    /// The number of turns the seed was given.
+    pub selected: u64,
+    /// The number of seeds added to the pool before this one, which identifies it in the pool.
+    pub added: u64,
+    /// The pool's clock when the seed was last selected, or added if it never was.
+    pub touched: u64,
+}
+
+impl<T, F> Seed<T, F> {
+    pub fn new(input: T, feedback: F) -> Seed<T, F> {
+        Seed { input, feedback, energy: 0, selected: 0, added: 0, touched: 0 }
+    }
+
+    /// The same seed with its feedback mapped by `f`.
+    pub(crate) fn map_feedback<G>(self, f: impl FnOnce(F) -> G) -> Seed<T, G> {
+        let Seed { input, feedback, energy, selected, added, touched } = self;
+        Seed { input, feedback: f(feedback), energy, selected, added, touched }
+    }
+}
+
+/// How many seeds a pool holds, and how many it dropped or turned away.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The number of seeds in the pool.
+    pub size: usize,
+    /// The number of seeds evicted, as counted by `SeedPool::evicted`.
+    pub evicted: u64,
+    /// The number of seeds rejected, as counted by `SeedPool::rejected`.
+    pub rejected: u64,
+}
+
+/// Which seed a full pool drops to make room for a new one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// The seed with the lowest feedback, the oldest first on ties.
+    #[default]
+    Worst,
+    /// The seed added first.
+    Oldest,
+    /// The seed whose last turn is the furthest back, counting additions as turns.
+    LeastRecentlySelected,
+}
+
+/// A node of a `Ranking`: a distinct feedback with the number of seeds having it, and the total
+/// count of its subtree.
+struct Node<F> {
+    feedback: F,
+    count: usize,
+    size: usize,
+    priority: u64,
+    left: Tree<F>,
+    right: Tree<F>,
+}
+
+type Tree<F> = Option<Box<Node<F>>>;
+
+fn size<F>(tree: &Tree<F>) -> usize {
+    tree.as_ref().map_or(0, |node| node.size)
+}
+
+impl<F> Node<F> {
+    fn update(&mut self) {
+        self.size = self.count + size(&self.left) + size(&self.right);
+    }
+}
+
+/// Joins two treaps, every feedback of `left` being below every feedback of `right`.
+fn merge<F>(left: Tree<F>, right: Tree<F>) -> Tree<F> {
+    match (left, right) {
+        (None, tree) | (tree, None) => tree,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update();
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update();
+                Some(right)
+            }
+        },
+    }
+}
+
+/// Splits a treap not holding `feedback` into the feedbacks below it and those above it.
+fn split<F: Ord>(tree: Tree<F>, feedback: &F) -> (Tree<F>, Tree<F>) {
+    let Some(mut node) = tree else {
+        return (None, None);
+    };
+    if node.feedback < *feedback {
+        let (below, above) = split(node.right.take(), feedback);
+        node.right = below;
+        node.update();
+        (Some(node), above)
+    } else {
+        let (below, above) = split(node.left.take(), feedback);
+        node.left = above;
+        node.update();
+        (below, Some(node))
+    }
+}
+
+/// Adds `delta` to the count of `feedback` if the treap holds it, dropping its node once no seed
+/// has it. Returns whether it was found.
+fn adjust<F: Ord>(tree: &mut Tree<F>, feedback: &F, delta: isize) -> bool {
+    let Some(node) = tree else {
+        return false;
+    };
+    let found = match feedback.cmp(&node.feedback) {
+        Ordering::Less => adjust(&mut node.left, feedback, delta),
+        Ordering::Greater => adjust(&mut node.right, feedback, delta),
+        Ordering::Equal => {
+            node.count = node.count.saturating_add_signed(delta);
+            if node.count == 0 {
+                let node = tree.take().unwrap();
+                *tree = merge(node.left, node.right);
+                return true;
+            }
+            true
+        },
+    };
+    if found {
+        node.update();
+    }
+    found
 }
 
+/// The number of seeds with each feedback, as a treap whose nodes know the size of their subtree,
+/// so that counting the seeds with a better feedback takes logarithmic time.
+struct Ranking<F> {
+    root: Tree<F>,
+    /// The number of nodes ever created, from which their priorities are drawn.
+    nodes: u64,
+}
+
+impl<F: Ord> Ranking<F> {
+    fn new() -> Ranking<F> {
+        Ranking { root: None, nodes: 0 }
+    }
+
+    fn insert(&mut self, feedback: F) {
+        if adjust(&mut self.root, &feedback, 1) {
+            return;
+        }
+        self.nodes += 1;
+        // The SplitMix64 finalizer spreads consecutive node numbers into random-looking priorities
+        let mut priority = self.nodes.wrapping_mul(0x9e3779b97f4a7c15);
+        priority = (priority ^ (priority >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        priority = (priority ^ (priority >> 27)).wrapping_mul(0x94d049bb133111eb);
+        let node = Node { feedback, count: 1, size: 1, priority, left: None, right: None };
+        let (below, above) = split(self.root.take(), &node.feedback);
+        self.root = merge(merge(below, Some(Box::new(node))), above);
+    }
+
+    fn remove(&mut self, feedback: &F) {
+        adjust(&mut self.root, feedback, -1);
+    }
+
+    /// The number of seeds with `feedback`.
+    fn count(&self, feedback: &F) -> usize {
+        let mut tree = &self.root;
+        while let Some(node) = tree {
+            tree = match feedback.cmp(&node.feedback) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return node.count,
+            };
+        }
+        0
+    }
+
+    /// The number of seeds with a feedback strictly above `feedback`.
+    fn above(&self, feedback: &F) -> usize {
+        let (mut tree, mut above) = (&self.root, 0);
+        while let Some(node) = tree {
+            tree = match feedback.cmp(&node.feedback) {
+                Ordering::Less => {
+                    above += node.count + size(&node.right);
+                    &node.left
+                },
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return above + size(&node.right),
+            };
+        }
+        above
+    }
+}
+
+/// The seeds of a targeting or fuzzing loop, indexed by feedback and by turn order so that
+/// adding, selecting, evicting, ranking and finding the best or worst seed take logarithmic time.
 pub struct SeedPool<T: Clone, F: Clone + Ord> {
-    pub seeds: Vec<Seed<T, F>>,
+    seeds: BTreeMap<u64, Seed<T, F>>,
     pub best_of_all_time: Option<Seed<T, F>>,
+    /// The seeds ordered by feedback, then by age.
+    by_feedback: BTreeSet<(F, u64)>,
+    /// The seeds in the order they get their next turn: the fewest turns first, then the best.
+    queue: BTreeSet<(u64, Reverse<F>, u64)>,
+    /// The number of seeds with each feedback.
+    frequencies: Ranking<F>,
+    /// The seeds ordered by the last time they were touched.
+    recency: BTreeSet<(u64, u64)>,
+    schedule: Arc<dyn Schedule>,
+    acceptance: Arc<dyn Acceptance>,
+    /// The seed whose turn it is.
+    current: Option<u64>,
+    /// The number of seeds ever added to the pool.
+    added: u64,
+    /// Ticks on every addition and selection.
+    clock: u64,
+    capacity: Option<usize>,
+    eviction: Eviction,
+    fingerprint: Option<Box<dyn Fn(&T) -> u64>>,
+    /// Keeps only the seeds whose feedback no other seed dominates, when set.
+    dominates: Option<fn(&F, &F) -> bool>,
+    /// The fingerprints of the seeds in the pool.
+    fingerprints: HashSet<u64>,
+    evicted: u64,
+    rejected: u64,
 }
 
 impl<T: Clone, F: Clone + Ord> SeedPool<T, F> {
@@ -15,42 +285,276 @@ impl<T: Clone, F: Clone + Ord> SeedPool<T, F> {
         SeedPool::default()
     }
 
-    pub fn add_seed(&mut self, seed: Seed<T, F>) {
-        if let Some(best) = &self.best_of_all_time {
-            if seed.feedback > best.feedback {
-                self.best_of_all_time = Some(seed.clone());
+    pub fn with_schedule(schedule: Arc<dyn Schedule>) -> SeedPool<T, F> {
+        SeedPool {
+            seeds: BTreeMap::new(),
+            best_of_all_time: None,
+            by_feedback: BTreeSet::new(),
+            queue: BTreeSet::new(),
+            frequencies: Ranking::new(),
+            recency: BTreeSet::new(),
+            schedule,
+            acceptance: Arc::new(Improvement),
+            current: None,
+            added: 0,
+            clock: 0,
+            capacity: None,
+            eviction: Eviction::default(),
+            fingerprint: None,
+            dominates: None,
+            fingerprints: HashSet::new(),
+            evicted: 0,
+            rejected: 0,
+        }
+    }
+
+    /// The pool used by the targeting and fuzzing loops run with `config`.
+    pub fn from_config(config: &Config) -> SeedPool<T, F>
+    where
+        T: 'static,
+    {
+        let mut pool = SeedPool::with_schedule(config.schedule.clone())
+            .with_acceptance(config.acceptance.clone());
+        if let Some(capacity) = config.pool_capacity.or(config.acceptance.capacity()) {
+            pool = pool.with_capacity(capacity, config.eviction);
+        }
+        if let Some(fingerprint) = config.fingerprint {
+            pool = pool.with_fingerprint(move |input| fingerprint(input));
+        }
+        pool
+    }
+
+    /// Sets the strategy deciding which seeds `accepts` keeps, `Improvement` by default.
+    pub fn with_acceptance(mut self, acceptance: Arc<dyn Acceptance>) -> SeedPool<T, F> {
+        self.acceptance = acceptance;
+        self
+    }
+
+    /// Whether a new seed with `feedback`, found at `iteration` of a loop, should be added to the
+    /// pool.
+    pub fn accepts(&self, feedback: &F, iteration: u64, rng: &mut TestRng) -> bool
+    where
+        F: Measure,
+    {
+        self.accepts_measured(feedback, |feedback| Some(feedback.measure()), iteration, rng)
+    }
+
+    /// Like `accepts`, for feedback measured by `measure` if it can be.
+    pub(crate) fn accepts_measured(
+        &self,
+        feedback: &F,
+        measure: impl Fn(&F) -> Option<f64>,
+        iteration: u64,
+        rng: &mut TestRng,
+    ) -> bool {
+        let best = self.by_feedback.last().map(|(best, _)| best);
+        let worst = self.by_feedback.first().map(|(worst, _)| worst);
+        let candidate = Candidate {
+            feedback: measure(feedback),
+            best: best.and_then(&measure),
+            parent: self.current().and_then(|seed| measure(&seed.feedback)),
+            improves: best.is_none_or(|best| feedback > best),
+            beats_worst: worst.is_none_or(|worst| feedback > worst),
+            pool_size: self.seeds.len(),
+            iteration,
+        };
+        self.acceptance.accept(&candidate, rng)
+    }
+
+    /// The seed whose turn it is, if it is still in the pool.
+    pub fn current(&self) -> Option<&Seed<T, F>> {
+        self.current.and_then(|id| self.seeds.get(&id))
+    }
+
+    /// Bounds the pool to `capacity` seeds (at least 1), evicting a seed chosen by `eviction`
+    /// whenever a new one would exceed it.
+    pub fn with_capacity(mut self, capacity: usize, eviction: Eviction) -> SeedPool<T, F> {
+        self.capacity = Some(capacity.max(1));
+        self.eviction = eviction;
+        self
+    }
+
+    /// Rejects seeds whose input has the same fingerprint as a seed of the pool.
+    pub fn with_fingerprint(mut self, fingerprint: impl Fn(&T) -> u64 + 'static) -> SeedPool<T, F> {
+        self.fingerprint = Some(Box::new(fingerprint));
+        self
+    }
+
+    /// Rejects seeds whose input hashes like the input of a seed of the pool.
+    pub fn deduplicated(self) -> SeedPool<T, F>
+    where
+        T: Hash,
+    {
+        self.with_fingerprint(|input| hash(input))
+    }
+
+    /// Keeps the pool a Pareto front: seeds dominated by a seed of the pool are rejected, and
+    /// adding a seed evicts the seeds it dominates. Dominance is checked against every seed.
+    pub fn nondominated(mut self) -> SeedPool<T, F>
+    where
+        F: Dominance,
+    {
+        self.dominates = Some(F::dominates);
+        self
+    }
+
+    /// Adds `seed` to the pool, evicting another one if the pool is full. Returns whether it was
+    /// added, rather than rejected as a duplicate or as dominated.
+    pub fn add_seed(&mut self, mut seed: Seed<T, F>) -> bool {
+        if let Some(dominates) = self.dominates {
+            if self.seeds.values().any(|other| dominates(&other.feedback, &seed.feedback)) {
+                self.rejected += 1;
+                return false;
             }
-        } else {
+        }
+        if let Some(fingerprint) = &self.fingerprint {
+            if !self.fingerprints.insert(fingerprint(&seed.input)) {
+                self.rejected += 1;
+                return false;
+            }
+        }
+        if let Some(dominates) = self.dominates {
+            let dominated: Vec<u64> = self
+                .seeds
+                .iter()
+                .filter(|(_, other)| dominates(&seed.feedback, &other.feedback))
+                .map(|(id, _)| *id)
+                .collect();
+            for id in dominated {
+                self.remove(id);
+                self.evicted += 1;
+            }
+        }
+        if self.best_of_all_time.as_ref().is_none_or(|best| seed.feedback > best.feedback) {
             self.best_of_all_time = Some(seed.clone());
         }
 
-        self.seeds.push(seed);
+        let id = self.added;
+        self.added += 1;
+        seed.added = id;
+        seed.touched = self.tick();
+        self.by_feedback.insert((seed.feedback.clone(), id));
+        self.queue.insert((seed.selected, Reverse(seed.feedback.clone()), id));
+        self.frequencies.insert(seed.feedback.clone());
+        self.recency.insert((seed.touched, id));
+        self.seeds.insert(id, seed);
+
+        if self.capacity.is_some_and(|capacity| self.seeds.len() > capacity) {
+            let victim = match self.eviction {
+                Eviction::Worst => self.by_feedback.first().map(|(_, id)| *id),
+                Eviction::Oldest => self.seeds.keys().next().copied(),
+                Eviction::LeastRecentlySelected => self.recency.first().map(|(_, id)| *id),
+            };
+            if let Some(victim) = victim {
+                self.remove(victim);
+                self.evicted += 1;
+            }
+        }
+        true
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Removes the seed `id` from the pool and its indexes.
+    fn remove(&mut self, id: u64) {
+        let Some(seed) = self.seeds.remove(&id) else {
+            return;
+        };
+        self.by_feedback.remove(&(seed.feedback.clone(), id));
+        self.queue.remove(&(seed.selected, Reverse(seed.feedback.clone()), id));
+        self.recency.remove(&(seed.touched, id));
+        self.frequencies.remove(&seed.feedback);
+        if let Some(fingerprint) = &self.fingerprint {
+            self.fingerprints.remove(&fingerprint(&seed.input));
+        }
+        if self.current == Some(id) {
+            self.current = None;
+        }
+    }
+
+    /// The number of seeds evicted to respect the capacity of the pool, or dominated by a new
+    /// seed.
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    /// The number of seeds rejected as duplicates, or as dominated.
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    /// The size of the pool and the counts of evicted and rejected seeds.
+    pub fn stats(&self) -> PoolStats {
+        PoolStats { size: self.seeds.len(), evicted: self.evicted, rejected: self.rejected }
+    }
+
+    /// The seeds of the pool, oldest first.
+    pub fn seeds(&self) -> impl Iterator<Item = &Seed<T, F>> {
+        self.seeds.values()
+    }
+
+    /// The seeds of the pool, best first.
+    fn ranked(&self) -> impl Iterator<Item = &Seed<T, F>> {
+        self.by_feedback.iter().rev().map(|(_, id)| &self.seeds[id])
     }
 
     pub fn best(&self) -> &Seed<T, F> {
-        self.seeds.iter().max_by_key(|s| &s.feedback).unwrap()
+        self.ranked().next().unwrap()
     }
 
-    pub fn worst(&self) -> &Seed<T, F> {
-        self.seeds.iter().min_by_key(|s| &s.feedback).unwrap()
+    /// The two seeds with the highest feedback, if the pool has at least two.
+    pub fn top_two(&self) -> Option<(&Seed<T, F>, &Seed<T, F>)> {
+        let mut ranked = self.ranked();
+        Some((ranked.next()?, ranked.next()?))
     }
 
-    pub fn pop(&mut self) -> Option<Seed<T, F>> {
-        if let Some((max_index, _)) = self.seeds.iter().enumerate().max_by_key(|(_, s)| &s.feedback)
-        {
-            let seed = self.seeds[max_index].clone();
+    pub fn worst(&self) -> &Seed<T, F> {
+        let (_, id) = self.by_feedback.first().unwrap();
+        &self.seeds[id]
+    }
 
-            if seed.energy > 0 {
-                self.seeds[max_index].energy -= 1;
-            } else {
-                self.seeds.remove(max_index);
-            }
-            Some(seed)
-        } else {
-            None
+    /// The standing of the seed `id`, for the schedule.
+    fn seed_stats(&self, id: u64) -> SeedStats {
+        let seed = &self.seeds[&id];
+        SeedStats {
+            rank: self.frequencies.above(&seed.feedback),
+            frequency: self.frequencies.count(&seed.feedback),
+            age: self.added - id - 1,
+            selected: seed.selected,
+            pool_size: self.seeds.len(),
         }
     }
 
+    /// The seed to mutate next. The current seed is returned until its energy runs out, and the
+    /// seed selected the fewest times, the best one first on ties, then gets a turn with the
+    /// energy given by the schedule.
+    pub fn pop(&mut self) -> Option<&Seed<T, F>> {
+        let current = self.current.filter(|id| self.seeds.get(id).is_some_and(|s| s.energy > 0));
+        let id = match current {
+            Some(id) => id,
+            None => {
+                let (selected, feedback, id) = self.queue.pop_first()?;
+                let energy = self.schedule.energy(&self.seed_stats(id)).max(1);
+                self.queue.insert((selected + 1, feedback, id));
+                let touched = self.tick();
+                let seed = self.seeds.get_mut(&id).unwrap();
+                self.recency.remove(&(seed.touched, id));
+                self.recency.insert((touched, id));
+                seed.touched = touched;
+                seed.energy = energy;
+                seed.selected += 1;
+                self.current = Some(id);
+                id
+            },
+        };
+        let seed = self.seeds.get_mut(&id).unwrap();
+        seed.energy -= 1;
+        Some(seed)
+    }
+
     pub fn size(&self) -> usize {
         self.seeds.len()
     }
@@ -62,6 +566,146 @@ impl<T: Clone, F: Clone + Ord> SeedPool<T, F> {
 
 impl<T: Clone, F: Clone + Ord> Default for SeedPool<T, F> {
     fn default() -> SeedPool<T, F> {
-        SeedPool { seeds: vec![], best_of_all_time: None }
+        SeedPool::with_schedule(Arc::new(Exploit::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            acceptance::Threshold,
+            schedule::Uniform,
+        },
+        rand::SeedableRng,
+    };
+
+    #[test]
+    fn test_pool_gives_every_seed_a_turn() {
+        let mut pool = SeedPool::with_schedule(Arc::new(Uniform { energy: 2 }));
+        for (input, feedback) in [('a', 1), ('b', 3), ('c', 2)] {
+            pool.add_seed(Seed::new(input, feedback));
+        }
+        let turns: String = (0..8).map(|_| pool.pop().unwrap().input).collect();
+        assert_eq!(turns, "bbccaabb");
+        assert!(pool.seeds().all(|seed| seed.selected >= 1));
+        assert!(SeedPool::<char, u8>::new().pop().is_none());
+    }
+
+    #[test]
+    fn test_pool_order() {
+        let mut pool = SeedPool::new();
+        for (input, feedback) in [('a', 2), ('b', 5), ('c', 1), ('d', 5), ('e', 2)] {
+            pool.add_seed(Seed::new(input, feedback));
+        }
+        assert_eq!((pool.best().input, pool.worst().input), ('d', 'c'));
+        let (first, second) = pool.top_two().unwrap();
+        assert_eq!((first.input, second.input), ('d', 'b'));
+        assert_eq!(pool.best_of_all_time.as_ref().unwrap().input, 'b');
+        assert_eq!(pool.seeds().map(|seed| seed.input).collect::<String>(), "abcde");
+
+        let stats = pool.seed_stats(4);
+        assert_eq!((stats.rank, stats.frequency, stats.age, stats.pool_size), (2, 2, 0, 5));
+    }
+
+    fn inputs(pool: &SeedPool<char, u8>) -> String {
+        pool.seeds().map(|seed| seed.input).collect()
+    }
+
+    #[test]
+    fn test_pool_eviction() {
+        let seeds = [('a', 2), ('b', 5), ('c', 1), ('d', 4)];
+
+        let mut pool = SeedPool::new().with_capacity(2, Eviction::Worst);
+        seeds
+            .into_iter()
+            .for_each(|(input, feedback)| _ = pool.add_seed(Seed::new(input, feedback)));
+        assert_eq!((inputs(&pool), pool.evicted()), ("bd".to_string(), 2));
+        assert_eq!(pool.worst().input, 'd');
+
+        let mut pool = SeedPool::new().with_capacity(2, Eviction::Oldest);
+        seeds
+            .into_iter()
+            .for_each(|(input, feedback)| _ = pool.add_seed(Seed::new(input, feedback)));
+        assert_eq!(inputs(&pool), "cd");
+        assert_eq!(pool.best().input, 'd');
+
+        let mut pool = SeedPool::new().with_capacity(2, Eviction::LeastRecentlySelected);
+        pool.add_seed(Seed::new('a', 2));
+        pool.add_seed(Seed::new('b', 5));
+        assert_eq!(pool.pop().unwrap().input, 'b');
+        pool.add_seed(Seed::new('c', 1));
+        assert_eq!(inputs(&pool), "bc");
+        // The evicted seed loses its turn
+        pool.add_seed(Seed::new('d', 0));
+        assert_eq!(inputs(&pool), "cd");
+        assert_eq!(pool.pop().unwrap().input, 'c');
+    }
+
+    #[test]
+    fn test_pool_deduplication() {
+        let mut pool = SeedPool::new().deduplicated().with_capacity(2, Eviction::Oldest);
+        assert!(pool.add_seed(Seed::new('a', 1)));
+        assert!(!pool.add_seed(Seed::new('a', 2)));
+        assert!(pool.add_seed(Seed::new('b', 2)));
+        assert!(pool.add_seed(Seed::new('c', 3)));
+        // Evicted inputs can be added again
+        assert!(pool.add_seed(Seed::new('a', 4)));
+        assert_eq!((inputs(&pool), pool.rejected(), pool.evicted()), ("ca".to_string(), 1, 2));
+
+        let mut pool =
+            SeedPool::new().with_fingerprint(|input: &char| input.is_ascii_digit() as u64);
+        assert!(pool.add_seed(Seed::new('1', 1)));
+        assert!(!pool.add_seed(Seed::new('2', 1)));
+        assert!(pool.add_seed(Seed::new('x', 1)));
+    }
+
+    #[test]
+    fn test_ranking() {
+        let mut ranking = Ranking::new();
+        let feedbacks = [5, 3, 8, 3, 1, 8, 8, 6, 2, 9, 3];
+        feedbacks.into_iter().for_each(|feedback| ranking.insert(feedback));
+        for feedback in 0..=10 {
+            let above = feedbacks.iter().filter(|other| **other > feedback).count();
+            let count = feedbacks.iter().filter(|other| **other == feedback).count();
+            assert_eq!((ranking.above(&feedback), ranking.count(&feedback)), (above, count));
+        }
+        ranking.remove(&8);
+        ranking.remove(&3);
+        ranking.remove(&3);
+        ranking.remove(&3);
+        assert_eq!((ranking.count(&3), ranking.count(&8)), (0, 2));
+        assert_eq!((ranking.above(&3), ranking.above(&2), ranking.above(&0)), (5, 5, 7));
+        assert_eq!(size(&ranking.root), 7);
+    }
+
+    #[test]
+    fn test_pool_acceptance() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let mut pool = SeedPool::new();
+        pool.add_seed(Seed::new('a', 10));
+        pool.add_seed(Seed::new('b', 12));
+        assert_eq!(pool.pop().unwrap().input, 'b');
+        assert!(!pool.accepts(&11, 3, &mut rng));
+
+        // A child slightly worse than the current seed is kept
+        let pool = pool.with_acceptance(Arc::new(Threshold { threshold: 2.0 }));
+        assert!(pool.accepts(&11, 3, &mut rng));
+        assert!(!pool.accepts(&9, 3, &mut rng));
+    }
+
+    #[test]
+    fn test_pool_front() {
+        let mut pool = SeedPool::new().nondominated();
+        assert!(pool.add_seed(Seed::new('a', vec![1, 1])));
+        assert!(pool.add_seed(Seed::new('b', vec![3, 0])));
+        assert!(pool.add_seed(Seed::new('c', vec![0, 3])));
+        assert!(!pool.add_seed(Seed::new('d', vec![1, 0])));
+        // Seeds of equal feedback both belong to the front
+        assert!(pool.add_seed(Seed::new('e', vec![0, 3])));
+        assert!(pool.add_seed(Seed::new('f', vec![2, 2])));
+        let inputs: String = pool.seeds().map(|seed| seed.input).collect();
+        assert_eq!((inputs.as_str(), pool.evicted(), pool.rejected()), ("bcef", 1, 1));
     }
 }
@@ -0,0 +1,330 @@
+use std::{
+    cell::RefCell,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt::{
+        self,
+        Debug,
+    },
+};
+
+/// The distribution of labels and tabulated values over the passing tests of a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// The number of passing tests the labels were counted over.
+    pub cases: u64,
+    /// The number of passing tests each label was attached to.
+    pub labels: BTreeMap<String, u64>,
+    /// The number of times each value was recorded in each named table.
+    pub tables: BTreeMap<String, BTreeMap<String, u64>>,
+    /// The percentage of passing tests each label is required to be attached to, declared with
+    /// `cover`.
+    pub coverage: BTreeMap<String, f64>,
+}
+
+/// The labels and tabulated values recorded while checking a single test case.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Case {
+    labels: BTreeSet<String>,
+    tables: Vec<(String, String)>,
+    coverage: Vec<(String, f64)>,
+}
+
+/// Whether the coverage requirements of a run hold, as judged by `Statistics::check_coverage`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Coverage {
+    /// Every requirement is met, up to `COVERAGE_TOLERANCE`.
+    Sufficient,
+    /// A label is attached to too few tests.
+    Insufficient { label: String, required: f64, observed: f64 },
+    /// More tests are needed to tell.
+    Undecided,
+}
+
+/// The z-score of the confidence intervals coverage is checked with, leaving a one in a billion
+/// chance of a wrong verdict (QuickCheck's default certainty).
+const COVERAGE_Z: f64 = 6.109;
+
+/// The fraction of a requirement a label needs to be confirmed to reach to be deemed sufficient,
+/// so that a generator hitting a requirement exactly does not test forever.
+const COVERAGE_TOLERANCE: f64 = 0.9;
+
+/// The Wilson score interval of the proportion of `k` successes out of `n` trials.
+fn wilson(k: u64, n: u64, z: f64) -> (f64, f64) {
+    let (k, n) = (k as f64, n as f64);
+    let p = k / n;
+    let z2 = z * z;
+    let center = p + z2 / (2.0 * n);
+    let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    let denominator = 1.0 + z2 / n;
+    ((center - margin) / denominator, (center + margin) / denominator)
+}
+
+impl Statistics {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.tables.is_empty() && self.coverage.is_empty()
+    }
+
+    /// The percentage of passing tests `label` was attached to.
+    pub fn percentage(&self, label: &str) -> f64 {
+        let count = self.labels.get(label).copied().unwrap_or(0);
+        100.0 * count as f64 / self.cases.max(1) as f64
+    }
+
+    /// Checks the coverage requirements against the tests so far: a requirement is confirmed once
+    /// the lower bound of the confidence interval of its label's proportion reaches
+    /// `COVERAGE_TOLERANCE` of it, and refuted once the upper bound falls short of it.
+    pub(crate) fn check_coverage(&self) -> Coverage {
+        if self.coverage.is_empty() {
+            return Coverage::Sufficient;
+        }
+        if self.cases == 0 {
+            return Coverage::Undecided;
+        }
+        let mut sufficient = true;
+        for (label, &required) in &self.coverage {
+            let count = self.labels.get(label).copied().unwrap_or(0);
+            let (low, high) = wilson(count, self.cases, COVERAGE_Z);
+            if 100.0 * high < required {
+                return Coverage::Insufficient {
+                    label: label.clone(),
+                    required,
+                    observed: self.percentage(label),
+                };
+            }
+            sufficient &= 100.0 * low >= COVERAGE_TOLERANCE * required;
+        }
+        if sufficient { Coverage::Sufficient } else { Coverage::Undecided }
+    }
+
+    /// Counts a passing test case.
+    pub(crate) fn add(&mut self, case: Case) {
+        self.cases += 1;
+        for label in case.labels {
+            *self.labels.entry(label).or_default() += 1;
+        }
+        for (table, value) in case.tables {
+            *self.tables.entry(table).or_default().entry(value).or_default() += 1;
+        }
+        self.coverage.extend(case.coverage);
+    }
+}
+
+/// Rows sorted by decreasing count, as a percentage of `total`.
+fn write_rows<'a>(
+    f: &mut fmt::Formatter<'_>,
+    rows: impl Iterator<Item = (&'a String, &'a u64)>,
+    total: u64,
+) -> fmt::Result {
+    let mut rows: Vec<_> = rows.collect();
+    rows.sort_by(|(a, x), (b, y)| y.cmp(x).then(a.cmp(b)));
+    for (name, count) in rows {
+        writeln!(f, "{:5.1}% {}", 100.0 * *count as f64 / total.max(1) as f64, name)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_rows(f, self.labels.iter(), self.cases)?;
+        for (table, values) in &self.tables {
+            let total = values.values().sum();
+            writeln!(f, "\n{} ({} in total):", table, total)?;
+            write_rows(f, values.iter(), total)?;
+        }
+        for (label, &required) in &self.coverage {
+            let observed = self.percentage(label);
+            if observed < required {
+                writeln!(f, "\nOnly {:.1}% {}, but expected {:.1}%", observed, label, required)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Case>> = const { RefCell::new(None) };
+}
+
+/// Runs `f`, returning the labels and values it recorded next to its result.
+pub(crate) fn record<T>(f: impl FnOnce() -> T) -> (T, Case) {
+    let previous = CURRENT.replace(Some(Case::default()));
+    let result = f();
+    let case = CURRENT.replace(previous).unwrap_or_default();
+    (result, case)
+}
+
+fn with_case(f: impl FnOnce(&mut Case)) {
+    CURRENT.with_borrow_mut(|case| {
+        if let Some(case) = case {
+            f(case)
+        }
+    });
+}
+
+/// Attaches a label to the current test case. Labels are counted once per test case, and have no
+/// effect outside of a property or while shrinking.
+pub fn label(label: impl Into<String>) {
+    let label = label.into();
+    with_case(|case| {
+        case.labels.insert(label);
+    });
+}
+
+/// Attaches `label` to the current test case if `condition` holds.
+pub fn classify(condition: bool, label: impl Into<String>) {
+    if condition {
+        self::label(label);
+    }
+}
+
+/// Attaches `label` to the current test case if `condition` holds, and requires it to be attached
+/// to at least `percentage` percent of the passing tests. `quickcheck` keeps testing past
+/// `max_tests` until every requirement is statistically confirmed, and fails with
+/// `ResultStatus::InsufficientCoverage` as soon as one is refuted. The other runners, including
+/// the parallel, profiling and property loops, stop at their usual budget and fail with it if a
+/// requirement is refuted by then.
+pub fn cover(percentage: f64, condition: bool, label: impl Into<String>) {
+    let label = label.into();
+    with_case(|case| {
+        if condition {
+            case.labels.insert(label.clone());
+        }
+        case.coverage.push((label, percentage));
+    });
+}
+
+/// Labels the current test case with the `Debug` representation of `value`.
+pub fn collect<T: Debug>(value: T) {
+    label(format!("{:?}", value));
+}
+
+/// Records `values` in the table named `table`, which is reported separately from the labels,
+/// with percentages relative to all values recorded in that table.
+pub fn tabulate<V: Into<String>>(table: impl Into<String>, values: impl IntoIterator<Item = V>) {
+    let table = table.into();
+    let values: Vec<String> = values.into_iter().map(Into::into).collect();
+    with_case(|case| {
+        case.tables.extend(values.into_iter().map(|value| (table.clone(), value)));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            config::Config,
+            parallelism::{
+                par_quickcheck,
+                par_quickcheck_with,
+            },
+            quickcheck::{
+                ResultStatus,
+                quickcheck_with,
+            },
+            targeting::prop_targeting_loop_with,
+        },
+    };
+
+    #[test]
+    fn test_statistics() {
+        let config = Config::new().with_seed(7);
+        let result = quickcheck_with(&config, |x: Vec<u8>| {
+            classify(x.is_empty(), "empty");
+            collect(x.len().min(2));
+            tabulate("elements", x.iter().map(|v| v.to_string()));
+            Some(true)
+        });
+        let statistics = result.statistics;
+        assert_eq!(statistics.cases, 100);
+        assert!(statistics.labels["empty"] > 0);
+        assert_eq!(statistics.labels["0"], statistics.labels["empty"]);
+        assert_eq!(statistics.labels.values().sum::<u64>(), 100 + statistics.labels["empty"]);
+        assert!(statistics.tables["elements"].values().sum::<u64>() > 0);
+        assert!(statistics.to_string().contains("% empty\n"));
+
+        // Only passing tests count, not the failing one nor the shrinking steps
+        let result = par_quickcheck(|x: &mut u8| {
+            label("checked");
+            *x < 3
+        });
+        assert!(matches!(result.status, ResultStatus::Failed { .. }));
+        assert_eq!(result.statistics.labels.get("checked").copied().unwrap_or(0), result.passed);
+
+        // Labels outside of a property are ignored
+        label("nowhere");
+        assert_eq!(record(|| ()).1, Case::default());
+    }
+
+    #[test]
+    fn test_statistics_display() {
+        let mut statistics = Statistics::default();
+        for i in 0..4 {
+            let labels = if i == 0 { vec!["a", "b"] } else { vec!["b"] };
+            let labels = labels.into_iter().map(String::from).collect();
+            let tables = vec![("t".into(), i.to_string())];
+            statistics.add(Case { labels, tables, coverage: vec![("a".into(), 50.0)] });
+        }
+        assert_eq!(
+            statistics.to_string(),
+            "100.0% b\n 25.0% a\n\nt (4 in total):\n 25.0% 0\n 25.0% 1\n 25.0% 2\n 25.0% 3\n\nOnly 25.0% a, but expected 50.0%\n"
+        );
+    }
+
+    #[test]
+    fn test_coverage() {
+        let config = Config::new().with_seed(3);
+        let result = quickcheck_with(&config, |x: Vec<u8>| {
+            cover(20.0, !x.is_empty(), "non-empty");
+            true
+        });
+        assert_eq!(result.status, ResultStatus::Finished);
+        assert!(result.statistics.percentage("non-empty") >= 20.0);
+
+        // Testing goes on past `max_tests` until the requirement is refuted
+        let result = quickcheck_with(&config, |x: Vec<u8>| {
+            cover(20.0, x.is_empty(), "empty");
+            true
+        });
+        let ResultStatus::InsufficientCoverage { label, required, observed } = result.status else {
+            panic!("expected insufficient coverage, got {:?}", result.status);
+        };
+        assert_eq!((label.as_str(), required), ("empty", 20.0));
+        assert!(observed < 20.0);
+        assert!(result.passed > 100);
+    }
+
+    #[test]
+    fn test_coverage_of_other_runners() {
+        let config = Config::new().with_seed(3);
+        let property = |x: &Vec<u8>| {
+            cover(90.0, x.is_empty(), "empty");
+            true
+        };
+        let result = par_quickcheck_with(&config, |x: &mut Vec<u8>| property(x));
+        assert!(matches!(result.status, ResultStatus::InsufficientCoverage { .. }));
+        assert_eq!(result.passed, 100);
+
+        let result =
+            prop_targeting_loop_with(&config, |x: Vec<u8>| property(&x), |x: Vec<u8>| x.len());
+        assert!(matches!(result.status, ResultStatus::InsufficientCoverage { .. }));
+
+        // Requirements that are not refuted by the end of the budget do not fail the run
+        let result = par_quickcheck_with(&config, |x: &mut Vec<u8>| {
+            cover(20.0, x.is_empty(), "empty");
+            true
+        });
+        assert_eq!(result.status, ResultStatus::Finished);
+    }
+
+    #[test]
+    fn test_wilson() {
+        let (low, high) = wilson(50, 100, 1.96);
+        assert!((low - 0.404).abs() < 0.001 && (high - 0.596).abs() < 0.001);
+        let (low, high) = wilson(0, 10, COVERAGE_Z);
+        assert!(low.abs() < 1e-9 && high < 1.0);
+    }
+}
@@ -0,0 +1,108 @@
+use {
+    crate::config::Config,
+    cpu_time::ThreadTime,
+    std::{
+        fmt,
+        time::Instant,
+    },
+};
+
+/// The criterion that ended a targeting or fuzzing loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// The loop ran `Config::max_tests` iterations.
+    Budget,
+    /// `Config::time_limit` of wall-clock time elapsed.
+    Time,
+    /// The thread running the loop spent `Config::cpu_time_limit` of CPU time since it started.
+    CpuTime,
+    /// No new best seed was found in the last `Config::plateau` iterations.
+    Plateau,
+    /// The best seed reached `Config::target`.
+    Target,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Budget => write!(f, "iteration budget exhausted"),
+            Stop::Time => write!(f, "time limit reached"),
+            Stop::CpuTime => write!(f, "CPU time limit reached"),
+            Stop::Plateau => write!(f, "no improvement on the plateau"),
+            Stop::Target => write!(f, "target reached"),
+        }
+    }
+}
+
+/// Checks the stopping criteria of a configuration over the iterations of a loop.
+pub(crate) struct Stopping<'a> {
+    config: &'a Config,
+    started: Instant,
+    cpu: ThreadTime,
+    /// The last iteration that found a new best seed.
+    improved: u64,
+}
+
+impl<'a> Stopping<'a> {
+    pub(crate) fn new(config: &'a Config) -> Stopping<'a> {
+        Stopping { config, started: Instant::now(), cpu: ThreadTime::now(), improved: 0 }
+    }
+
+    /// Records that the `test`th iteration found a new best seed.
+    pub(crate) fn improved(&mut self, test: u64) {
+        self.improved = test;
+    }
+
+    /// The criterion ending the loop after `tests` iterations, if any, given the score of its
+    /// best seed as measured by `Objective::score`.
+    pub(crate) fn check(&self, tests: u64, best: Option<f64>) -> Option<Stop> {
+        let config = self.config;
+        if tests >= config.max_tests {
+            Some(Stop::Budget)
+        } else if config.time_limit.is_some_and(|limit| self.started.elapsed() >= limit) {
+            Some(Stop::Time)
+        } else if config.cpu_time_limit.is_some_and(|limit| self.cpu.elapsed() >= limit) {
+            Some(Stop::CpuTime)
+        } else if config.plateau.is_some_and(|plateau| tests - self.improved >= plateau) {
+            Some(Stop::Plateau)
+        } else if config
+            .target
+            .zip(best)
+            .is_some_and(|(target, best)| best >= config.objective.score(&target))
+        {
+            Some(Stop::Target)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::feedback::Objective,
+        std::time::Duration,
+    };
+
+    #[test]
+    fn test_stopping() {
+        let config = Config::default().with_max_tests(100).with_plateau(10).with_target(5.0);
+        let mut stopping = Stopping::new(&config);
+        assert_eq!(stopping.check(0, None), None);
+        assert_eq!(stopping.check(100, None), Some(Stop::Budget));
+        stopping.improved(20);
+        assert_eq!(stopping.check(29, Some(4.0)), None);
+        assert_eq!(stopping.check(30, Some(4.0)), Some(Stop::Plateau));
+        assert_eq!(stopping.check(29, Some(5.0)), Some(Stop::Target));
+
+        // Targets are reached as measured by the objective
+        let config = config.with_objective(Objective::Minimize);
+        let stopping = Stopping::new(&config);
+        assert_eq!(stopping.check(1, Some(Objective::Minimize.score(&6.0))), None);
+        assert_eq!(stopping.check(1, Some(Objective::Minimize.score(&4.0))), Some(Stop::Target));
+
+        let config = Config::default().with_time_limit(Duration::ZERO);
+        assert_eq!(Stopping::new(&config).check(0, None), Some(Stop::Time));
+    }
+}
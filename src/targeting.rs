@@ -1,113 +1,799 @@
 use {
     crate::{
+        config::Config,
+        feedback::{
+            Measurer,
+            Objective,
+            Oriented,
+        },
+        panic::{
+            Panic,
+            catch,
+        },
         quickcheck::{
             Arbitrary,
+            Crossover,
             Mutate,
             ResultStatus,
             RunResult,
+            Shrink,
+            shrink_failure,
+        },
+        reporter::{
+            Event,
+            report,
+            report_stop,
         },
+        rng::TestRng,
         seedpool::{
+            PoolStats,
             Seed,
             SeedPool,
         },
+        statistics::{
+            Statistics,
+            record,
+        },
+        stopping::{
+            Stop,
+            Stopping,
+        },
+        testable::{
+            Property,
+            TestResult,
+            Testable,
+            check,
+            fails,
+            failure,
+        },
+    },
+    rand::{
+        Rng,
+        SeedableRng,
     },
-    rand::rngs::ThreadRng,
     std::fmt::Debug,
 };
 
+/// The result of an optimizing targeting or fuzzing loop.
+#[derive(Clone, Debug)]
+pub struct Optimized<T, F> {
+    /// The seed with the best feedback found, unless the pool never kept one: every feedback was
+    /// discarded as NaN, or the loop stopped before its first iteration.
+    pub best: Option<Seed<T, F>>,
+    /// The seed pool as the loop ended.
+    pub pool: PoolStats,
+    /// The criterion that stopped the loop, unless a panic did.
+    pub stop: Option<Stop>,
+    /// The input the property or its feedback panicked on, and the panic, which ended the loop.
+    pub panic: Option<(T, Panic)>,
+}
+
+impl<T: Debug, F> Optimized<T, F> {
+    /// The best seed of a loop run with the default configuration, which keeps the first seed,
+    /// raising again the panic that ended the loop if any.
+    pub(crate) fn into_best(self) -> Seed<T, F> {
+        if let Some((input, panic)) = self.panic {
+            panic!("the property panicked on {:?}: {}", input, panic);
+        }
+        self.best.expect("the default configuration keeps the first seed")
+    }
+}
+
+/// The result of `pareto_targeting_loop_with`.
+#[derive(Clone, Debug)]
+pub struct Front<T, F> {
+    /// The seeds no other seed dominates, oldest first.
+    pub seeds: Vec<Seed<T, Vec<F>>>,
+    /// The seed pool as the loop ended.
+    pub pool: PoolStats,
+    /// The criterion that stopped the loop, unless a panic did.
+    pub stop: Option<Stop>,
+    /// The input the property or its feedback panicked on, and the panic, which ended the loop.
+    pub panic: Option<(T, Panic)>,
+}
+
+/// Crosses two seeds over into a child, as `Crossover::crossover` does for the loops crossing
+/// seeds over.
+pub(crate) type Cross<Domain> = fn(&Domain, &Domain, &mut TestRng) -> Domain;
+
+/// The next input of a targeting or fuzzing loop: if it crosses seeds over with `cross`, at
+/// `config.crossover_rate` a child of the two best seeds of the pool, otherwise a mutation of a
+/// popped seed, or a fresh input while the pool is empty.
+pub(crate) fn next_input<Domain, Feedback>(
+    config: &Config,
+    pool: &mut SeedPool<Domain, Feedback>,
+    rng: &mut TestRng,
+    size: usize,
+    cross: Option<Cross<Domain>>,
+) -> Domain
+where
+    Domain: Clone + Arbitrary<TestRng> + Mutate<TestRng>,
+    Feedback: Clone + Ord,
+{
+    if let Some(cross) = cross.filter(|_| rng.random_bool(config.crossover_rate)) {
+        if let Some((first, second)) = pool.top_two() {
+            return cross(&first.input, &second.input, rng);
+        }
+    }
+    match pool.pop() {
+        Some(seed) => Domain::mutate(&seed.input, rng, size),
+        None => Domain::generate(rng, size),
+    }
+}
+
+/// The measurer of the feedback of a loop run with `config`, checking that the loop has one if it
+/// aims at a target, as set by `objectives` or `target`.
+pub(crate) fn feedback_measurer<Feedback: 'static>(
+    config: &Config,
+    objectives: &[Objective],
+    target: Option<f64>,
+) -> Option<Measurer> {
+    let measurer = config.measurer::<Feedback>();
+    let targets = target.is_some() || objectives.iter().any(|o| matches!(o, Objective::Target(_)));
+    assert!(
+        measurer.is_some() || !targets,
+        "targets need feedback implementing `Measure`, registered with `Config::with_measure`"
+    );
+    measurer
+}
+
+/// Adds the seed with `feedback` found at the `test`th iteration of a loop to its pool, if the
+/// NaN policy and the acceptance strategy of `config` keep it, and reports it if no seed before
+/// was better. Returns whether it was.
+pub(crate) fn add_seed<Domain, Feedback>(
+    config: &Config,
+    pool: &mut SeedPool<Domain, Oriented<Feedback>>,
+    rng: &mut TestRng,
+    test: u64,
+    input: Domain,
+    feedback: Oriented<Feedback>,
+) -> bool
+where
+    Domain: Clone + Debug,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+{
+    if !config.nan.admits(&feedback.feedback)
+        || !pool.accepts_measured(&feedback, |feedback| feedback.score, test, rng)
+    {
+        return false;
+    }
+    let improves = pool.best_of_all_time.as_ref().is_none_or(|best| feedback > best.feedback);
+    if pool.add_seed(Seed::new(input, feedback)) && improves {
+        let best = pool.best_of_all_time.as_ref().unwrap();
+        config.reporter.report(&Event::NewBest {
+            test,
+            input: &best.input,
+            feedback: &best.feedback.feedback,
+            pool_size: pool.size(),
+        });
+        return true;
+    }
+    false
+}
+
 pub fn maximizing_targeting_loop<
-    Domain: Clone + Debug + Arbitrary<ThreadRng> + Mutate<ThreadRng>,
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
     Codomain,
-    Feedback: Clone + Ord + Debug,
+    Feedback: Clone + PartialOrd + Debug + 'static,
 >(
-    f: fn(Domain) -> Codomain,
-    fb: fn(Domain, Codomain) -> Feedback,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
 ) -> Seed<Domain, Feedback> {
-    let mut pool: SeedPool<Domain, Feedback> = SeedPool::new();
-    let fuel = 1000;
-    let mut rng = rand::rng();
-
-    for i in 1..=fuel {
-        if i % 1000 == 0 {
-            println!("Iteration: {}", i);
-            println!("Pool size: {}", pool.size());
-            println!("Best of all time: {:?}", pool.best_of_all_time.clone().unwrap().feedback);
-            println!("====================\n");
-        }
-        let input = if let Some(seed) = pool.pop() {
-            Domain::mutate(&seed.input, &mut rng, (i as f32).log2() as usize)
-        } else {
-            Domain::generate(&mut rng, (i as f32).log2() as usize)
+    maximizing_targeting_loop_with(&Config::default().with_max_tests(1000), f, fb).into_best()
+}
+
+pub fn maximizing_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Optimized<Domain, Feedback> {
+    optimizing_targeting_loop_with(&config.clone().with_objective(Objective::Maximize), f, fb)
+}
+
+pub fn minimizing_targeting_loop<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Seed<Domain, Feedback> {
+    minimizing_targeting_loop_with(&Config::default().with_max_tests(1000), f, fb).into_best()
+}
+
+pub fn minimizing_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Optimized<Domain, Feedback> {
+    optimizing_targeting_loop_with(&config.clone().with_objective(Objective::Minimize), f, fb)
+}
+
+/// The targeting loop optimizing the feedback for `config.objective`, returning the best seed
+/// found.
+pub fn optimizing_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Optimized<Domain, Feedback> {
+    targeting_loop(config, None, f, fb)
+}
+
+/// Like `optimizing_targeting_loop_with`, but crosses the two best seeds of the pool over instead
+/// of mutating one at `config.crossover_rate`.
+pub fn crossover_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Crossover<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Optimized<Domain, Feedback> {
+    targeting_loop(config, Some(Domain::crossover), f, fb)
+}
+
+fn targeting_loop<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    cross: Option<Cross<Domain>>,
+    mut f: impl Property<Domain, Codomain>,
+    mut fb: impl FnMut(Domain, Codomain) -> Feedback,
+) -> Optimized<Domain, Feedback> {
+    let measurer = feedback_measurer::<Feedback>(config, &[config.objective], config.target);
+    let mut pool: SeedPool<Domain, Oriented<Feedback>> = SeedPool::from_config(config);
+    let mut rng = TestRng::seed_from_u64(config.run_seed());
+
+    let mut stopping = Stopping::new(config);
+    let mut i = 0;
+    let (stop, panic) = loop {
+        let best = pool.best_of_all_time.as_ref().and_then(|best| best.feedback.score);
+        if let Some(stop) = stopping.check(i, best) {
+            break (Some(stop), None);
+        }
+        i += 1;
+        let input = next_input(config, &mut pool, &mut rng, config.size(i), cross);
+        config.reporter.report(&Event::Started { test: i, input: &input });
+
+        let feedback = match catch(|| fb(input.clone(), f(input.clone()))) {
+            Ok(feedback) => Oriented::new(feedback, config.objective, measurer),
+            Err(panic) => break (None, Some((input, panic))),
         };
+        if add_seed(config, &mut pool, &mut rng, i, input, feedback) {
+            stopping.improved(i);
+        }
+    };
+
+    config.reporter.report(&Event::Finished { tests: i, stop, result: None });
+    let stats = pool.stats();
+    let best = pool.best_of_all_time.map(|best| best.map_feedback(|oriented| oriented.feedback));
+    Optimized { best, pool: stats, stop, panic }
+}
+
+pub fn pareto_targeting_loop<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    objectives: &[Objective],
+    f: impl Property<Domain, Codomain>,
+    fb: impl FnMut(Domain, Codomain) -> Vec<Feedback>,
+) -> Front<Domain, Feedback> {
+    pareto_targeting_loop_with(&Config::default().with_max_tests(1000), objectives, f, fb)
+}
 
-        let result = f(input.clone());
-        let feedback = fb(input.clone(), result);
+/// The targeting loop for feedback made of several objectives, the `i`th feedback being optimized
+/// for `objectives[i]`. Its pool only keeps the seeds no other seed dominates, ignoring
+/// `config.acceptance` and `config.target`, and the loop returns this Pareto front, oldest seed
+/// first.
+pub fn pareto_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + 'static,
+    Codomain,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+>(
+    config: &Config,
+    objectives: &[Objective],
+    mut f: impl Property<Domain, Codomain>,
+    mut fb: impl FnMut(Domain, Codomain) -> Vec<Feedback>,
+) -> Front<Domain, Feedback> {
+    let measurer = feedback_measurer::<Feedback>(config, objectives, None);
+    let mut pool: SeedPool<Domain, Vec<Oriented<Feedback>>> =
+        SeedPool::from_config(config).nondominated();
+    let mut rng = TestRng::seed_from_u64(config.run_seed());
 
-        if pool.is_empty() || feedback > pool.best().clone().feedback {
-            let seed = Seed { input, feedback, energy: 1000 };
-            pool.add_seed(seed);
+    let mut stopping = Stopping::new(config);
+    let mut i = 0;
+    // Fronts have no best seed to reach a target with
+    let (stop, panic) = loop {
+        if let Some(stop) = stopping.check(i, None) {
+            break (Some(stop), None);
         }
-    }
+        i += 1;
+        let input = next_input(config, &mut pool, &mut rng, config.size(i), None);
+        config.reporter.report(&Event::Started { test: i, input: &input });
 
-    pool.best_of_all_time.unwrap().clone()
+        let feedback = match catch(|| fb(input.clone(), f(input.clone()))) {
+            Ok(feedback) => feedback,
+            Err(panic) => break (None, Some((input, panic))),
+        };
+        assert_eq!(feedback.len(), objectives.len(), "expected one feedback per objective");
+        if !config.nan.admits(&feedback) {
+            continue;
+        }
+        let oriented = feedback
+            .iter()
+            .zip(objectives)
+            .map(|(x, o)| Oriented::new(x.clone(), *o, measurer))
+            .collect();
+
+        if pool.add_seed(Seed::new(input.clone(), oriented)) {
+            stopping.improved(i);
+            config.reporter.report(&Event::NewBest {
+                test: i,
+                input: &input,
+                feedback: &feedback,
+                pool_size: pool.size(),
+            });
+        }
+    };
+
+    config.reporter.report(&Event::Finished { tests: i, stop, result: None });
+    let seeds = pool
+        .seeds()
+        .cloned()
+        .map(|seed| {
+            seed.map_feedback(|feedback| feedback.into_iter().map(|x| x.feedback).collect())
+        })
+        .collect();
+    Front { seeds, pool: pool.stats(), stop, panic }
 }
 
 pub fn prop_targeting_loop<
-    Domain: Clone + Debug + Arbitrary<ThreadRng> + Mutate<ThreadRng>,
-    Feedback: Clone + Ord + Debug,
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
 >(
-    f: fn(Domain) -> bool,
-    fb: fn(Domain) -> Feedback,
+    f: impl Property<Domain, P>,
+    fb: impl FnMut(Domain) -> Feedback,
 ) -> RunResult {
-    let mut pool: SeedPool<Domain, Feedback> = SeedPool::new();
-    let fuel = 100000;
-    let mut rng = rand::rng();
-
-    for i in 1..=fuel {
-        if i % 1000 == 0 {
-            println!("Iteration: {}", i);
-            println!("Pool size: {}", pool.size());
-            println!("Best of all time: {:?}", pool.best_of_all_time.clone().unwrap().feedback);
-            println!("====================\n");
-        }
-        let input = if let Some(seed) = pool.pop() {
-            Domain::mutate(&seed.input, &mut rng, (i as f32).log2() as usize)
-        } else {
-            Domain::generate(&mut rng, (i as f32).log2() as usize)
-        };
+    prop_targeting_loop_with(&Config::default().with_max_tests(100000), f, fb)
+}
 
-        let result = f(input.clone());
-        let feedback = fb(input.clone());
+pub fn prop_targeting_loop_with<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+>(
+    config: &Config,
+    f: impl Property<Domain, P>,
+    fb: impl FnMut(Domain) -> Feedback,
+) -> RunResult {
+    prop_targeting(config, None, f, fb)
+}
 
-        if !result {
-            return RunResult {
-                status: ResultStatus::Failed { arguments: vec![format!("{:?}", input)] },
-                passed: i,
-                discarded: 0,
-            };
+/// Like `prop_targeting_loop_with`, but crosses the two best seeds of the pool over instead of
+/// mutating one at `config.crossover_rate`.
+pub fn prop_crossover_targeting_loop_with<Domain, Feedback, P>(
+    config: &Config,
+    f: impl Property<Domain, P>,
+    fb: impl FnMut(Domain) -> Feedback,
+) -> RunResult
+where
+    Domain: Clone
+        + Debug
+        + Arbitrary<TestRng>
+        + Mutate<TestRng>
+        + Crossover<TestRng>
+        + Shrink
+        + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+{
+    prop_targeting(config, Some(Domain::crossover), f, fb)
+}
+
+fn prop_targeting<
+    Domain: Clone + Debug + Arbitrary<TestRng> + Mutate<TestRng> + Shrink + 'static,
+    Feedback: Clone + PartialOrd + Debug + 'static,
+    P: Testable,
+>(
+    config: &Config,
+    cross: Option<Cross<Domain>>,
+    mut f: impl Property<Domain, P>,
+    mut fb: impl FnMut(Domain) -> Feedback,
+) -> RunResult {
+    let measurer = feedback_measurer::<Feedback>(config, &[config.objective], config.target);
+    let mut pool: SeedPool<Domain, Oriented<Feedback>> = SeedPool::from_config(config);
+    let seed = config.run_seed();
+    let mut rng = TestRng::seed_from_u64(seed);
+    let mut discarded = 0;
+    let mut statistics = Statistics::default();
+
+    let mut stopping = Stopping::new(config);
+    let mut i = 0;
+    let stop = loop {
+        let best = pool.best_of_all_time.as_ref().and_then(|best| best.feedback.score);
+        if let Some(stop) = stopping.check(i, best) {
+            break stop;
+        }
+        i += 1;
+        let input = match catch(|| next_input(config, &mut pool, &mut rng, config.size(i), cross)) {
+            Ok(input) => input,
+            Err(panic) => {
+                return report(
+                    config,
+                    RunResult {
+                        passed: i - 1 - discarded,
+                        discarded,
+                        shrinks: 0,
+                        seed,
+                        replay: None,
+                        status: ResultStatus::Aborted { error: format!("generator {}", panic) },
+                        statistics,
+                        pool: Some(pool.stats()),
+                        stop: None,
+                    },
+                );
+            },
+        };
+
+        config.reporter.report(&Event::Started { test: i, input: &input });
+        let (checked, labels) = record(|| check(&mut f, input.clone()));
+        if fails(&checked) {
+            let (input, shrinks) = shrink_failure(config, i, input, |candidate| {
+                fails(&check(&mut f, candidate.clone()))
+            });
+            let checked = check(&mut f, input.clone());
+            return report(
+                config,
+                RunResult {
+                    status: failure(&input, checked),
+                    passed: i - 1 - discarded,
+                    discarded,
+                    shrinks,
+                    seed,
+                    replay: None,
+                    statistics,
+                    pool: Some(pool.stats()),
+                    stop: None,
+                },
+            );
+        }
+        // Discarded inputs give no feedback
+        if checked == Ok(TestResult::Discarded) {
+            discarded += 1;
+            config.reporter.report(&Event::Discarded { test: i });
+            continue;
         }
+        statistics.add(labels);
+        config.reporter.report(&Event::Passed { test: i });
 
-        if pool.is_empty() || feedback > pool.best().clone().feedback {
-            let seed = Seed { input, feedback, energy: 1000 };
-            pool.add_seed(seed);
+        let feedback = Oriented::new(fb(input.clone()), config.objective, measurer);
+        if add_seed(config, &mut pool, &mut rng, i, input, feedback) {
+            stopping.improved(i);
         }
-    }
+    };
 
-    RunResult { passed: fuel, discarded: 0, status: ResultStatus::Finished }
+    report_stop(
+        config,
+        RunResult {
+            passed: i - discarded,
+            discarded,
+            shrinks: 0,
+            seed,
+            replay: None,
+            status: ResultStatus::Finished,
+            statistics,
+            pool: Some(pool.stats()),
+            stop: None,
+        },
+        stop,
+    )
 }
 
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {
+        super::*,
+        crate::{
+            acceptance::{
+                Annealing,
+                Threshold,
+                TopK,
+            },
+            feedback::Nan,
+            schedule::{
+                Exploit,
+                Explore,
+                Fast,
+                Rare,
+                Uniform,
+            },
+            seedpool::Eviction,
+        },
+        std::{
+            cmp::Reverse,
+            time::Duration,
+        },
+    };
 
     #[test]
     fn test_maximizing_targeting_loop() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let result = maximizing_targeting_loop_with(
+            &config,
+            |x: Vec<u8>| x.iter().map(|v| *v as u64).sum(),
+            |_x: Vec<u8>, y: u64| y,
+        );
+
+        // A random input of the largest size the loop reaches
+        let size = config.size(1000);
+        let mut rng = TestRng::seed_from_u64(1);
+        let avg: u64 = <Vec<u8>>::generate(&mut rng, size).iter().map(|v| *v as u64).sum();
+
+        let best = result.best.unwrap();
+        assert!(best.feedback > avg);
+        assert_eq!(best.input.len(), size);
+    }
+
+    /// A domain without a `Crossover` impl.
+    #[derive(Clone, Debug)]
+    struct Byte(u8);
+
+    impl<R: Rng> Arbitrary<R> for Byte {
+        fn generate(rng: &mut R, n: usize) -> Byte {
+            Byte(u8::generate(rng, n))
+        }
+    }
+
+    impl<R: Rng> Mutate<R> for Byte {
+        fn mutate(&self, rng: &mut R, n: usize) -> Byte {
+            Byte(self.0.mutate(rng, n))
+        }
+    }
+
+    #[test]
+    fn test_crossover_is_opt_in() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        assert_eq!(
+            maximizing_targeting_loop_with(&config, |x: Byte| x.0, |_x, y| y)
+                .best
+                .unwrap()
+                .feedback,
+            255
+        );
+
+        // Once the pool holds two seeds, every input is a child of the two best
+        let config = config.with_crossover_rate(1.0);
+        let sum = |x: Vec<u8>| x.iter().map(|v| *v as u64).sum::<u64>();
+        let mutated = maximizing_targeting_loop_with(&config, sum, |_x, y| y);
+        let crossed = crossover_targeting_loop_with(&config, sum, |_x, y| y);
+        assert_ne!(crossed.best.unwrap().input, mutated.best.unwrap().input);
+    }
+
+    #[test]
+    fn test_objectives_drive_targeting() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let sum = |x: Vec<u8>| x.iter().map(|v| *v as u64).sum::<u64>();
+
+        // Minimizing reversed feedback explores exactly like maximizing it
+        let maximized = maximizing_targeting_loop_with(&config, sum, |_x: Vec<u8>, y: u64| y);
+        let minimized =
+            minimizing_targeting_loop_with(&config, sum, |_x: Vec<u8>, y: u64| Reverse(y));
+        assert_eq!(maximized.best.unwrap().input, minimized.best.unwrap().input);
+        assert_eq!(
+            minimizing_targeting_loop_with(&config, sum, |_x, y| y).best.unwrap().feedback,
+            0
+        );
+
+        let targeted = optimizing_targeting_loop_with(
+            &config.with_objective(Objective::Target(500.0)),
+            sum,
+            |_x: Vec<u8>, y: u64| y,
+        );
+        let best = targeted.best.unwrap();
+        assert!(best.feedback.abs_diff(500) < 10, "{:?}", best);
+    }
+
+    #[test]
+    fn test_float_feedback() {
+        // The mean of an empty vector is NaN
+        let mean = |x: Vec<u8>| x.iter().map(|v| *v as f64).sum::<f64>() / x.len() as f64;
+        for nan in [Nan::Worst, Nan::Discard] {
+            let config = Config::default().with_max_tests(1000).with_seed(0).with_nan(nan);
+            let result = minimizing_targeting_loop_with(&config, mean, |_x: Vec<u8>, y: f64| y);
+            let best = result.best.unwrap();
+            assert!(best.feedback < 10.0, "{:?}", best);
+        }
+    }
+
+    #[test]
+    fn test_panicking_property() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let result = maximizing_targeting_loop_with(&config, |x: u8| assert!(x < 200), |x, ()| x);
+        let (input, panic) = result.panic.unwrap();
+        assert!(input >= 200, "{}", input);
+        assert!(panic.message.contains("x < 200"), "{}", panic);
+        assert_eq!(result.stop, None);
+
+        // The loops returning their best seed raise the panic again
+        let caught = catch(|| maximizing_targeting_loop(|x: u8| assert!(x < 200), |x, ()| x));
+        assert!(caught.unwrap_err().message.starts_with("the property panicked on"));
+    }
+
+    #[test]
+    fn test_no_best_seed() {
+        // Every feedback is discarded, so the pool never keeps a seed
+        let config = Config::default().with_max_tests(10).with_seed(0).with_nan(Nan::Discard);
+        let result = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, _y| f64::NAN);
+        assert!(result.best.is_none());
+        assert_eq!(result.pool.size, 0);
+    }
+
+    #[test]
+    fn test_pareto_targeting_loop() {
+        // Greater sums need longer vectors
+        let front = pareto_targeting_loop_with(
+            &Config::default().with_max_tests(1000).with_seed(0),
+            &[Objective::Maximize, Objective::Minimize],
+            |x: Vec<u8>| x.iter().map(|v| *v as u64).sum::<u64>(),
+            |x: Vec<u8>, sum: u64| vec![sum, x.len() as u64],
+        );
+        assert!(front.seeds.len() > 2, "{:?}", front);
+        assert_eq!(front.pool.size, front.seeds.len());
+        for seed in &front.seeds {
+            assert_eq!(seed.feedback[1], seed.input.len() as u64);
+            assert!(front.seeds.iter().all(|other| {
+                other.feedback[0] <= seed.feedback[0] || other.feedback[1] > seed.feedback[1]
+            }));
+        }
+    }
+
+    #[test]
+    fn test_stopping_criteria() {
+        let config = Config::default().with_max_tests(10000).with_seed(0);
+
+        // Only the first seed improves on the feedback
+        let result =
+            prop_targeting_loop_with(&config.clone().with_plateau(10), |_x: u8| true, |_x: u8| 0);
+        assert_eq!((result.status, result.passed), (ResultStatus::Finished, 11));
+        assert_eq!(result.stop, Some(Stop::Plateau));
+
         let result =
-            maximizing_targeting_loop(|x: Vec<i32>| x.iter().sum(), |_x: Vec<i32>, y: i32| y);
+            prop_targeting_loop_with(&config.clone().with_target(200.0), |_x: u8| true, |x: u8| x);
+        assert!(result.passed < 10000, "{}", result.passed);
+        assert_eq!(result.stop, Some(Stop::Target));
+
+        let result = prop_targeting_loop_with(
+            &config.clone().with_time_limit(Duration::ZERO),
+            |_x: u8| true,
+            |x: u8| x,
+        );
+        assert_eq!((result.status, result.passed), (ResultStatus::TimedOut, 0));
+        assert_eq!(result.stop, Some(Stop::Time));
+
+        // Optimizing loops stopped before their first iteration have no best seed
+        for (config, stop) in [
+            (config.clone().with_time_limit(Duration::ZERO), Stop::Time),
+            (config.with_plateau(0), Stop::Plateau),
+        ] {
+            let result = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, y| y);
+            assert!(result.best.is_none());
+            assert_eq!(result.stop, Some(stop));
+        }
+    }
+
+    #[test]
+    fn test_deduplication() {
+        // Every seed is kept, however bad
+        let config = Config::default()
+            .with_max_tests(1000)
+            .with_seed(0)
+            .with_acceptance(Threshold { threshold: 1000.0 });
+        let result = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, y| y);
+        assert_eq!((result.pool.size, result.pool.rejected), (1000, 0));
 
-        let avg: i32 = <Vec<i32>>::generate(&mut rand::rng(), 100).iter().sum();
+        let hashing = config.clone().with_hashing::<u8>();
+        let result = maximizing_targeting_loop_with(&hashing, |x: u8| x, |_x, y| y);
+        assert_eq!(result.pool.size, 222);
+        assert_eq!(result.pool.size as u64 + result.pool.rejected, 1000);
 
-        assert!(result.feedback > avg);
+        let result = prop_targeting_loop_with(&hashing, |_x: u8| true, |x: u8| x);
+        assert!(result.pool.is_some_and(|pool| pool.rejected > 0), "{:?}", result.pool);
+
+        // Inputs differing only in their lowest bit have the same fingerprint
+        let ranges = config.with_fingerprint(|x| *x.downcast_ref::<u8>().unwrap() as u64 / 2);
+        let result = maximizing_targeting_loop_with(&ranges, |x: u8| x, |_x, y| y);
+        assert_eq!(result.pool.size, 126);
+    }
+
+    #[test]
+    fn test_schedules_drive_targeting() {
+        let config = Config::default()
+            .with_max_tests(1000)
+            .with_pool_capacity(4)
+            .with_eviction(Eviction::LeastRecentlySelected);
+        let configs = [
+            config.clone().with_schedule(Uniform::default()),
+            config.clone().with_schedule(Exploit::default()),
+            config.clone().with_schedule(Explore::default()),
+            config.clone().with_schedule(Fast::default()),
+            config.with_schedule(Rare::default()),
+        ];
+        for config in configs {
+            let result = maximizing_targeting_loop_with(
+                &config,
+                |x: Vec<u8>| x.iter().map(|v| *v as u64).sum(),
+                |_x: Vec<u8>, y: u64| y,
+            );
+            assert_eq!(
+                result.best.unwrap().input.len(),
+                config.size(1000),
+                "{:?}",
+                config.schedule
+            );
+        }
+    }
+
+    #[test]
+    fn test_acceptance_keeps_non_improving_seeds() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+
+        // Only the first seed improves on a constant feedback
+        let result = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, _y| 0);
+        assert_eq!(result.pool.size, 1);
+        let top = config.clone().with_acceptance(TopK { k: 8 });
+        let result = maximizing_targeting_loop_with(&top, |x: u8| x, |_x, _y| 0);
+        assert_eq!(result.pool.size, 8);
+
+        // At most 256 seeds improve on each other, so the others were kept despite being no better
+        // than the seeds before them
+        let threshold = config.clone().with_acceptance(Threshold { threshold: 255.0 });
+        let result = maximizing_targeting_loop_with(&threshold, |x: u8| x, |_x, y| y);
+        assert_eq!(result.pool.size, 1000);
+        let annealing =
+            config.with_acceptance(Annealing { temperature: 1000.0, ..Annealing::default() });
+        let result = maximizing_targeting_loop_with(&annealing, |x: u8| x, |_x, y| y);
+        assert!(result.pool.size > 256, "{:?}", result.pool);
+    }
+
+    /// Feedback without a `Measure` implementation.
+    #[derive(Clone, Debug, PartialEq, PartialOrd)]
+    struct Score(u8);
+
+    #[test]
+    fn test_unmeasured_feedback() {
+        let config = Config::default().with_max_tests(1000).with_seed(0);
+        let best = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, y| Score(y)).best;
+        assert_eq!(best.unwrap().feedback, Score(255));
+
+        // Strategies needing a measure only keep improvements
+        let improvements = maximizing_targeting_loop_with(&config, |x: u8| x, |_x, y| y).pool;
+        let threshold = config.clone().with_acceptance(Threshold { threshold: 255.0 });
+        let result = maximizing_targeting_loop_with(&threshold, |x: u8| x, |_x, y| Score(y));
+        assert_eq!(result.pool, improvements);
+    }
+
+    #[test]
+    #[should_panic(expected = "targets need feedback implementing `Measure`")]
+    fn test_unmeasured_feedback_cannot_target() {
+        let config = Config::default().with_objective(Objective::Target(3.0));
+        optimizing_targeting_loop_with(&config, |x: u8| x, |_x, y| Score(y));
     }
 }
@@ -0,0 +1,207 @@
+use {
+    crate::{
+        panic::{
+            Panic,
+            catch,
+        },
+        quickcheck::ResultStatus,
+    },
+    std::fmt::{
+        Debug,
+        Display,
+    },
+};
+
+/// The outcome of a single test case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestResult {
+    Passed,
+    /// The input did not satisfy a precondition, so the case does not count.
+    Discarded,
+    /// The property does not hold, with an optional explanation.
+    Failed {
+        message: Option<String>,
+    },
+}
+
+impl TestResult {
+    pub fn failed(message: impl Into<String>) -> TestResult {
+        TestResult::Failed { message: Some(message.into()) }
+    }
+
+    pub fn from_bool(holds: bool) -> TestResult {
+        if holds { TestResult::Passed } else { TestResult::Failed { message: None } }
+    }
+}
+
+/// The values a property can return. Panicking properties fail whatever their return type.
+pub trait Testable {
+    fn result(self) -> TestResult;
+}
+
+impl Testable for TestResult {
+    fn result(self) -> TestResult {
+        self
+    }
+}
+
+impl Testable for bool {
+    fn result(self) -> TestResult {
+        TestResult::from_bool(self)
+    }
+}
+
+/// `None` discards the case, as returned by `Implies::implies` when the precondition fails.
+impl Testable for Option<bool> {
+    fn result(self) -> TestResult {
+        self.map_or(TestResult::Discarded, TestResult::from_bool)
+    }
+}
+
+/// A property that only fails by panicking, e.g. through `assert!`.
+impl Testable for () {
+    fn result(self) -> TestResult {
+        TestResult::Passed
+    }
+}
+
+impl<E: Display> Testable for Result<(), E> {
+    fn result(self) -> TestResult {
+        match self {
+            Ok(()) => TestResult::Passed,
+            Err(error) => TestResult::failed(error.to_string()),
+        }
+    }
+}
+
+/// Runs a property on `input`, catching panics.
+pub(crate) fn check<T, P: Testable>(f: impl FnOnce(T) -> P, input: T) -> Result<TestResult, Panic> {
+    catch(|| f(input).result())
+}
+
+/// Whether a checked property failed, by returning a failure or by panicking.
+pub(crate) fn fails(checked: &Result<TestResult, Panic>) -> bool {
+    matches!(checked, Ok(TestResult::Failed { .. }) | Err(_))
+}
+
+/// The status of a run that failed on `input`, given the result of checking the property on it.
+pub(crate) fn failure<T: Debug>(input: &T, checked: Result<TestResult, Panic>) -> ResultStatus {
+    let arguments = vec![format!("{:?}", input)];
+    match checked {
+        Ok(TestResult::Failed { message }) => {
+            ResultStatus::Failed { arguments, message, panic: None }
+        },
+        Err(panic) => ResultStatus::Failed { arguments, message: None, panic: Some(panic) },
+        // A flaky property that passed when it was rerun
+        Ok(_) => ResultStatus::Failed { arguments, message: None, panic: None },
+    }
+}
+
+/// A property checked by the sequential runners: a function or closure taking a generated input.
+///
+/// Closures can capture their environment, such as a model, a configuration or a reference
+/// implementation, and may mutate it between test cases.
+pub trait Property<T, Output>: FnMut(T) -> Output {}
+
+impl<T, Output, F: FnMut(T) -> Output> Property<T, Output> for F {}
+
+/// A property checked by the parallel runners, which call it from several threads at once.
+pub trait ParProperty<T, Output>: Fn(&mut T) -> Output + Sync {}
+
+impl<T, Output, F: Fn(&mut T) -> Output + Sync> ParProperty<T, Output> for F {}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            config::Config,
+            parallelism::{
+                par_quickcheck,
+                par_quickcheck_with,
+            },
+            quickcheck::{
+                Implies,
+                quickcheck,
+                quickcheck_with,
+            },
+            targeting::prop_targeting_loop_with,
+        },
+    };
+
+    #[test]
+    fn test_closures_capture_environment() {
+        let config = Config::new().with_max_tests(50);
+
+        // Sequential runners accept `FnMut` closures
+        let mut calls = 0;
+        let result = quickcheck_with(&config, |_: u8| {
+            calls += 1;
+            Some(true)
+        });
+        assert_eq!(result.status, ResultStatus::Finished);
+        assert_eq!(calls, 50);
+
+        let limit = vec![0u8; 10].len();
+        let result =
+            prop_targeting_loop_with(&config, |x: Vec<u8>| x.len() <= limit, |x: Vec<u8>| x.len());
+        assert_eq!(result.status, ResultStatus::Finished);
+
+        // Parallel runners accept `Fn + Sync` closures borrowing from the stack
+        let model: Vec<i32> = (0..10).collect();
+        let result = par_quickcheck(|x: &mut i32| *x < 0 || model.contains(&(*x % 10)));
+        assert_eq!(result.status, ResultStatus::Finished);
+    }
+
+    #[test]
+    fn test_failure_messages() {
+        let result =
+            quickcheck(|x: u8| if x < 3 { Ok(()) } else { Err(format!("{} is too big", x)) });
+        let message = Some("3 is too big".to_string());
+        let arguments = vec!["3".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message, panic: None });
+
+        let result =
+            quickcheck(
+                |x: u8| {
+                    if x == 0 { TestResult::Discarded } else { TestResult::failed("nonzero") }
+                },
+            );
+        let ResultStatus::Failed { arguments, message, .. } = result.status else {
+            panic!("expected a failure, got {:?}", result.status);
+        };
+        assert_eq!((arguments, message), (vec!["1".to_string()], Some("nonzero".to_string())));
+
+        // `()` properties fail by panicking
+        let result = par_quickcheck(|x: &mut u8| assert!(*x < 5));
+        let ResultStatus::Failed { arguments, message: None, panic: Some(_) } = result.status
+        else {
+            panic!("expected a panic, got {:?}", result.status);
+        };
+        assert_eq!(arguments, vec!["5".to_string()]);
+
+        let config = Config::new().with_max_tests(1000);
+        let result = prop_targeting_loop_with(
+            &config,
+            |x: Vec<u8>| if x.len() < 5 { Ok(()) } else { Err("too long") },
+            |x: Vec<u8>| x.len(),
+        );
+        let ResultStatus::Failed { message, .. } = result.status else {
+            panic!("expected a failure, got {:?}", result.status);
+        };
+        assert_eq!(message.as_deref(), Some("too long"));
+    }
+
+    #[test]
+    fn test_par_quickcheck_discards() {
+        let result = par_quickcheck(|x: &mut u8| x.is_multiple_of(2).implies(true));
+        assert_eq!(result.status, ResultStatus::Finished);
+        assert_eq!(result.passed, 100);
+        assert!(result.discarded > 0);
+
+        let config = Config::new().with_max_tests(10).with_max_discard_ratio(2);
+        let result = par_quickcheck_with(&config, |x: &mut u16| (*x == 12345).implies(true));
+        assert_eq!(result.status, ResultStatus::GaveUp);
+        assert_eq!(result.passed, 0);
+    }
+}
@@ -0,0 +1,297 @@
+use {
+    crate::quickcheck::{
+        Arbitrary,
+        Crossover,
+        Mutate,
+        Shrink,
+        weighted_index,
+    },
+    rand::Rng,
+    std::{
+        iter,
+        rc::Rc,
+    },
+};
+
+/// The characters text generators draw from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Alphabet {
+    /// Any ASCII character, including control characters.
+    Ascii,
+    /// Printable ASCII characters, from `' '` to `'~'`.
+    Printable,
+    /// Any Unicode scalar value, biased towards ASCII, combining marks, and the code points around
+    /// surrogates, byte-order marks and the end of the code space.
+    Unicode,
+    /// Only the given characters. Without any, only empty strings are generated.
+    Chars(Vec<char>),
+}
+
+/// Code points next to the boundaries where text handling tends to go wrong.
+const BOUNDARY_CHARS: [char; 8] =
+    ['\u{7f}', '\u{80}', '\u{7ff}', '\u{800}', '\u{d7ff}', '\u{e000}', '\u{feff}', '\u{fffd}'];
+
+impl Alphabet {
+    /// Whether the alphabet has no characters to draw from.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Alphabet::Chars(chars) if chars.is_empty())
+    }
+
+    /// A character from this alphabet, which must not be empty.
+    pub fn char<R: Rng>(&self, rng: &mut R) -> char {
+        match self {
+            Alphabet::Ascii => rng.random_range('\0'..='\u{7f}'),
+            Alphabet::Printable => rng.random_range(' '..='~'),
+            Alphabet::Unicode => {
+                match rng.random_range(0..10) {
+                    0..=3 => rng.random_range(' '..='~'),
+                    4 => rng.random_range('\0'..='\u{1f}'),
+                    // Combining diacritical marks
+                    5 => rng.random_range('\u{300}'..='\u{36f}'),
+                    6 => BOUNDARY_CHARS[rng.random_range(0..BOUNDARY_CHARS.len())],
+                    7 | 8 => rng.random_range('\u{80}'..='\u{d7ff}'),
+                    _ => rng.random_range('\u{e000}'..=char::MAX),
+                }
+            },
+            Alphabet::Chars(chars) => {
+                assert!(!chars.is_empty(), "cannot draw a character from an empty alphabet");
+                chars[rng.random_range(0..chars.len())]
+            },
+        }
+    }
+
+    /// A string of at most `n` characters from this alphabet.
+    pub fn string<R: Rng>(&self, rng: &mut R, n: usize) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let len = rng.random_range(0..=n);
+        (0..len).map(|_| self.char(rng)).collect()
+    }
+
+    /// Inserts, deletes, replaces or splices a few characters of `text`, drawing new ones from
+    /// this alphabet. An empty alphabet only deletes or splices, and the text only grows up to `n`
+    /// characters.
+    pub fn mutate<R: Rng>(&self, text: &str, rng: &mut R, n: usize) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+        let len = chars.len();
+        if len == 0 {
+            let count = if self.is_empty() { 0 } else { rng.random_range(0..=n.min(3)) };
+            return (0..count).map(|_| self.char(rng)).collect();
+        }
+
+        // Operators only grow the text up to the size `n`, like `VecMutation`, and inserting and
+        // replacing need characters to draw
+        let room = n.saturating_sub(len);
+        let drawing = if self.is_empty() { 0 } else { 1 };
+        let growing = if room > 0 { 1 } else { 0 };
+        match weighted_index(rng, &[drawing * growing, 1, drawing, growing]) {
+            0 => {
+                // Insert a few characters
+                let at = rng.random_range(0..=len);
+                let count = rng.random_range(1..=room.min(3));
+                let inserted = (0..count).map(|_| self.char(rng)).collect::<Vec<_>>();
+                chars.splice(at..at, inserted);
+            },
+            1 => {
+                // Delete a few characters
+                let start = rng.random_range(0..len);
+                let end = rng.random_range(start + 1..=len.min(start + 3));
+                chars.drain(start..end);
+            },
+            2 => {
+                // Replace a character
+                let at = rng.random_range(0..len);
+                chars[at] = self.char(rng);
+            },
+            _ => {
+                // Copy a chunk of the text elsewhere
+                let start = rng.random_range(0..len);
+                let end = rng.random_range(start + 1..=len.min(start + room));
+                let chunk = chars[start..end].to_vec();
+                let at = rng.random_range(0..=len);
+                chars.splice(at..at, chunk);
+            },
+        }
+
+        chars.into_iter().collect()
+    }
+}
+
+/// The candidates of `Shrink for Vec<char>`, generated lazily from characters the iterator owns.
+fn shrink_text(text: &str) -> Box<dyn Iterator<Item = String>> {
+    let chars: Rc<[char]> = text.chars().collect();
+    let len = chars.len();
+
+    // First try removing chunks of halving sizes, then shrinking single characters
+    let chunks = iter::successors(Some(len), |k| Some(k / 2)).take_while(|k| *k > 0);
+    let removals = {
+        let chars = chars.clone();
+        chunks.flat_map(move |k| {
+            let chars = chars.clone();
+            (0..=len - k)
+                .step_by(k)
+                .map(move |start| chars[..start].iter().chain(&chars[start + k..]).collect())
+        })
+    };
+    let elements = (0..len).flat_map(move |i| {
+        let chars = chars.clone();
+        // A character has a handful of candidates
+        let candidates: Vec<char> = chars[i].shrink().collect();
+        candidates.into_iter().map(move |c| {
+            let mut copy = chars.to_vec();
+            copy[i] = c;
+            copy.into_iter().collect()
+        })
+    });
+    Box::new(removals.chain(elements))
+}
+
+/// Single-point crossover on characters, like `Crossover for Vec<T>`.
+fn cross_text<R: Rng>(text: &str, other: &str, rng: &mut R) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    chars.crossover(&other.chars().collect(), rng).into_iter().collect()
+}
+
+impl<R: Rng> Arbitrary<R> for String {
+    fn generate(rng: &mut R, n: usize) -> String {
+        Alphabet::Unicode.string(rng, n)
+    }
+}
+
+impl<R: Rng> Mutate<R> for String {
+    fn mutate(&self, rng: &mut R, n: usize) -> String {
+        Alphabet::Unicode.mutate(self, rng, n)
+    }
+}
+
+impl<R: Rng> Crossover<R> for String {
+    fn crossover(&self, other: &String, rng: &mut R) -> String {
+        cross_text(self, other, rng)
+    }
+}
+
+impl Shrink for String {
+    fn shrink(&self) -> Box<dyn Iterator<Item = String>> {
+        shrink_text(self)
+    }
+}
+
+macro_rules! alphabet_string {
+    ($(#[$doc:meta])* $name:ident, $alphabet:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub String);
+
+        impl<R: Rng> Arbitrary<R> for $name {
+            fn generate(rng: &mut R, n: usize) -> $name {
+                $name($alphabet.string(rng, n))
+            }
+        }
+
+        impl<R: Rng> Mutate<R> for $name {
+            fn mutate(&self, rng: &mut R, n: usize) -> $name {
+                $name($alphabet.mutate(&self.0, rng, n))
+            }
+        }
+
+        impl<R: Rng> Crossover<R> for $name {
+            fn crossover(&self, other: &$name, rng: &mut R) -> $name {
+                $name(cross_text(&self.0, &other.0, rng))
+            }
+        }
+
+        impl Shrink for $name {
+            fn shrink(&self) -> Box<dyn Iterator<Item = $name>> {
+                Box::new(shrink_text(&self.0).map($name))
+            }
+        }
+    };
+}
+
+alphabet_string!(
+    /// A string of ASCII characters, including control characters.
+    AsciiString,
+    Alphabet::Ascii
+);
+alphabet_string!(
+    /// A string of printable ASCII characters.
+    PrintableString,
+    Alphabet::Printable
+);
+alphabet_string!(
+    /// A string of arbitrary Unicode scalar values; the same distribution `String` uses.
+    UnicodeString,
+    Alphabet::Unicode
+);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            quickcheck::{
+                ResultStatus,
+                quickcheck,
+            },
+            rng::TestRng,
+        },
+        rand::SeedableRng,
+    };
+
+    #[test]
+    fn test_alphabets() {
+        let mut rng = TestRng::seed_from_u64(0);
+        for _ in 0..100 {
+            assert!(AsciiString::generate(&mut rng, 20).0.is_ascii());
+            let printable = PrintableString::generate(&mut rng, 20);
+            assert!(printable.0.chars().all(|c| (' '..='~').contains(&c)));
+
+            let digits = Alphabet::Chars(vec!['0', '1']);
+            let mutated = digits.mutate(&digits.string(&mut rng, 20), &mut rng, 20);
+            assert!(mutated.chars().all(|c| c == '0' || c == '1'));
+        }
+
+        let text: String = (0..100).map(|_| String::generate(&mut rng, 20)).collect();
+        assert!(!text.is_ascii());
+
+        // Nothing to draw from: strings stay empty, or only lose or repeat their characters
+        let empty = Alphabet::Chars(vec![]);
+        assert_eq!(empty.string(&mut rng, 20), "");
+        assert_eq!(empty.mutate("", &mut rng, 20), "");
+        for _ in 0..100 {
+            assert!(empty.mutate("ab", &mut rng, 20).chars().all(|c| c == 'a' || c == 'b'));
+        }
+    }
+
+    #[test]
+    fn test_mutation_is_bounded() {
+        let mut rng = TestRng::seed_from_u64(0);
+        let mut text = String::new();
+        for _ in 0..1000 {
+            text = text.mutate(&mut rng, 20);
+            assert!(text.chars().count() <= 20, "{:?}", text);
+        }
+
+        // Text already past the size only shrinks or keeps its length
+        let long = "a".repeat(30);
+        for _ in 0..100 {
+            assert!(long.mutate(&mut rng, 20).chars().count() <= 30);
+        }
+    }
+
+    #[test]
+    fn test_shrink_text() {
+        let text = "aB c";
+        let chars: Vec<char> = text.chars().collect();
+        let expected: Vec<String> = chars.shrink().map(|c| c.into_iter().collect()).collect();
+        assert_eq!(text.to_string().shrink().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_quickcheck_string_shrinks() {
+        let result = quickcheck(|s: PrintableString| Some(s.0.len() < 3));
+        let arguments = vec!["PrintableString(\"aaa\")".to_string()];
+        assert_eq!(result.status, ResultStatus::Failed { arguments, message: None, panic: None });
+    }
+}